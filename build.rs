@@ -0,0 +1,79 @@
+use serde_json::Value;
+use std::env;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed=LIC_BUNDLED_DATA");
+    if env::var_os("CARGO_FEATURE_BUNDLED").is_some() {
+        bundle();
+    }
+}
+
+/// Embeds the `json` directory of a spdx/license-list-data checkout into
+/// `$OUT_DIR/bundled.rs`, which is included by `src/spdx/bundled.rs`.
+fn bundle() {
+    let data_dir = env::var_os("LIC_BUNDLED_DATA")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| {
+            Path::new(&env::var_os("CARGO_MANIFEST_DIR").unwrap()).join("license-list-data/json")
+        });
+    println!("cargo:rerun-if-changed={}", data_dir.display());
+
+    let licenses = read_json(&data_dir.join("licenses.json"));
+    let version = licenses["licenseListVersion"]
+        .as_str()
+        .expect("licenses.json has no licenseListVersion");
+
+    let mut code = String::new();
+    writeln!(code, "const LICENSE_LIST_VERSION: &str = {version:?};").unwrap();
+    writeln!(code, "const LICENSES: &str = {:?};", licenses.to_string()).unwrap();
+    writeln!(code, "const DETAILS: &[(&str, &str)] = &[").unwrap();
+    for license in licenses["licenses"].as_array().unwrap() {
+        let id = license["licenseId"].as_str().unwrap();
+        let mut details = read_json(&data_dir.join("details").join(format!("{id}.json")));
+        // The HTML renderings are never used and would double the binary size.
+        details["licenseTextHtml"] = Value::String(String::new());
+        if let Some(details) = details.as_object_mut() {
+            details.remove("standardLicenseHeaderHtml");
+        }
+        writeln!(code, "    ({id:?}, {:?}),", details.to_string()).unwrap();
+    }
+    writeln!(code, "];").unwrap();
+
+    let exceptions = read_json(&data_dir.join("exceptions.json"));
+    writeln!(
+        code,
+        "const EXCEPTIONS: &str = {:?};",
+        exceptions.to_string()
+    )
+    .unwrap();
+    writeln!(code, "const EXCEPTION_DETAILS: &[(&str, &str)] = &[").unwrap();
+    for exception in exceptions["exceptions"].as_array().unwrap() {
+        let id = exception["licenseExceptionId"].as_str().unwrap();
+        let mut details = read_json(&data_dir.join("exceptions").join(format!("{id}.json")));
+        if let Some(details) = details.as_object_mut() {
+            details.remove("exceptionTextHtml");
+        }
+        writeln!(code, "    ({id:?}, {:?}),", details.to_string()).unwrap();
+    }
+    writeln!(code, "];").unwrap();
+
+    let out_dir = PathBuf::from(env::var_os("OUT_DIR").unwrap());
+    fs::write(out_dir.join("bundled.rs"), code).unwrap();
+    println!("cargo:rustc-env=LIC_BUNDLED_LIST_VERSION={version}");
+}
+
+fn read_json(path: &Path) -> Value {
+    let text = fs::read_to_string(path).unwrap_or_else(|e| {
+        panic!(
+            "Cannot read {}: {e}\n\
+             The `bundled` feature needs a checkout of https://github.com/spdx/license-list-data, \
+             clone it into `license-list-data/` or point `LIC_BUNDLED_DATA` to its `json` directory.",
+            path.display()
+        )
+    });
+    serde_json::from_str(&text).unwrap_or_else(|e| panic!("Invalid {}: {e}", path.display()))
+}
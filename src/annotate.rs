@@ -0,0 +1,273 @@
+//! Inserting and updating the `SPDX-License-Identifier` header of a source file.
+
+use crate::comment::CommentStyle;
+
+pub const IDENTIFIER: &str = "SPDX-License-Identifier:";
+
+/// How many lines from the top are searched for an existing header.
+const HEADER_LINES: usize = 20;
+
+#[derive(Debug, PartialEq)]
+pub enum Change {
+    /// The header has been inserted, resulting in the new text
+    Added(String),
+    /// The existing header has been updated, resulting in the new text
+    Updated(String),
+    Unchanged,
+}
+
+/// What may precede the identifier on the line of a header: the start of a
+/// comment, or nothing inside a block comment.
+const COMMENT_PREFIXES: &[&str] = &[
+    "", "//", "///", "//!", "#", "*", "/*", "/**", "<!--", "--", "%", ";",
+];
+
+/// Byte offset of the `SPDX-License-Identifier:` of `line`, if it is in a
+/// comment and not, e.g., in a string literal.
+fn identifier_start(line: &str) -> Option<usize> {
+    let start = line.find(IDENTIFIER)?;
+    COMMENT_PREFIXES
+        .contains(&line[..start].trim())
+        .then_some(start)
+}
+
+/// Lines which have to stay at the top of a file, before the header.
+fn is_prolog(index: usize, line: &str) -> bool {
+    // https://peps.python.org/pep-0263/
+    let encoding = index < 2 && line.starts_with('#') && line.contains("coding");
+    (index == 0 && line.starts_with("#!")) || encoding || line.starts_with("<?xml")
+}
+
+/// Replaces the end of `line` from `start` by `value`, keeping the line ending
+/// and the end of a block comment, e.g. ` */`.
+fn replace_value(line: &str, start: usize, value: &str) -> String {
+    let body = line[start..].trim_end_matches(['\r', '\n']);
+    let eol = &line[start + body.len()..];
+    let end = ["*/", "-->"]
+        .into_iter()
+        .find(|end| body.trim_end().ends_with(end))
+        .map(|end| format!(" {end}"))
+        .unwrap_or_default();
+    format!("{}{value}{end}{eol}", &line[..start])
+}
+
+/// Adds the `SPDX-License-Identifier: <expression>` line, preceded by the
+/// `copyright` line if any, to the top of `text` in the comment syntax of
+/// its language. If `text` already has such a header, its values are
+/// updated instead.
+pub fn annotate(
+    text: &str,
+    style: CommentStyle,
+    expression: &str,
+    copyright: Option<&str>,
+) -> Change {
+    let lines: Vec<&str> = text.split_inclusive('\n').collect();
+    let head = &lines[..lines.len().min(HEADER_LINES)];
+    let Some((index, start)) = head
+        .iter()
+        .enumerate()
+        .find_map(|(index, line)| Some((index, identifier_start(line)?)))
+    else {
+        return Change::Added(insert_header(&lines, text, style, expression, copyright));
+    };
+
+    let mut lines: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+    let line = &lines[index];
+    let prefix = line[..start].to_string();
+    lines[index] = replace_value(line, start + IDENTIFIER.len(), &format!(" {expression}"));
+    if let Some(copyright) = copyright {
+        let existing = lines[..index]
+            .iter()
+            .rposition(|line| line.to_lowercase().contains("copyright"));
+        match existing {
+            // Only the `Copyright ...` lines we write are updated, not the
+            // `SPDX-FileCopyrightText` ones or the copyright of a standard header.
+            Some(i)
+                if lines[i]
+                    .get(prefix.len()..)
+                    .is_some_and(|line| line.starts_with("Copyright ")) =>
+            {
+                lines[i] = replace_value(&lines[i], prefix.len(), copyright);
+            }
+            Some(_) => {}
+            None => {
+                let line = replace_value(&lines[index], prefix.len(), copyright);
+                lines.insert(index, line);
+            }
+        }
+    }
+    let updated = lines.concat();
+    if updated == text {
+        Change::Unchanged
+    } else {
+        Change::Updated(updated)
+    }
+}
+
+/// The expression of the `SPDX-License-Identifier` header of `text`, if any.
+pub fn identifier(text: &str) -> Option<String> {
+    let (line, start) = text
+        .lines()
+        .take(HEADER_LINES)
+        .find_map(|line| Some((line, identifier_start(line)?)))?;
+    let value = &line[start + IDENTIFIER.len()..];
+    let value = value.trim();
+    let value = ["*/", "-->"]
+        .into_iter()
+        .find_map(|end| value.strip_suffix(end))
+        .unwrap_or(value);
+    Some(value.trim().to_string())
+}
+
+/// Replaces the expression of the `SPDX-License-Identifier` header of
+/// `text`, `None` if it has no such header.
+pub fn replace_identifier(text: &str, expression: &str) -> Option<String> {
+    let mut lines: Vec<String> = text.split_inclusive('\n').map(String::from).collect();
+    let (index, start) = lines
+        .iter()
+        .take(HEADER_LINES)
+        .enumerate()
+        .find_map(|(index, line)| Some((index, identifier_start(line)?)))?;
+    let start = start + IDENTIFIER.len();
+    lines[index] = replace_value(&lines[index], start, &format!(" {expression}"));
+    Some(lines.concat())
+}
+
+fn insert_header(
+    lines: &[&str],
+    text: &str,
+    style: CommentStyle,
+    expression: &str,
+    copyright: Option<&str>,
+) -> String {
+    let prolog = lines
+        .iter()
+        .enumerate()
+        .take_while(|(index, line)| is_prolog(*index, line))
+        .count();
+    let mut header = String::new();
+    if let Some(copyright) = copyright {
+        header += copyright;
+        header += "\n";
+    }
+    header += &format!("{IDENTIFIER} {expression}");
+    let mut header = style.comment(&header);
+    if prolog < lines.len() {
+        header += "\n";
+    }
+    if text.contains("\r\n") {
+        header = header.replace('\n', "\r\n");
+    }
+    let mut annotated = lines[..prolog].concat();
+    if !annotated.is_empty() && !annotated.ends_with('\n') {
+        annotated += "\n";
+    }
+    annotated += &header;
+    annotated += &lines[prolog..].concat();
+    annotated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::comment::Lang;
+
+    const COPYRIGHT: Option<&str> = Some("Copyright (c) 2023 Nyakku Shigure");
+
+    #[test]
+    fn test_add() {
+        let rust = Lang::Rust.comment_style();
+        assert_eq!(
+            annotate("fn main() {}\n", rust, "MIT", COPYRIGHT),
+            Change::Added(
+                "// Copyright (c) 2023 Nyakku Shigure\n// SPDX-License-Identifier: MIT\n\nfn main() {}\n"
+                    .into()
+            )
+        );
+        assert_eq!(
+            annotate("", rust, "MIT", None),
+            Change::Added("// SPDX-License-Identifier: MIT\n".into())
+        );
+        assert_eq!(
+            annotate("fn main() {}\r\n", rust, "MIT", None),
+            Change::Added("// SPDX-License-Identifier: MIT\r\n\r\nfn main() {}\r\n".into())
+        );
+    }
+
+    #[test]
+    fn test_add_after_prolog() {
+        let python = Lang::Python.comment_style();
+        let text = "#!/usr/bin/env python\n# -*- coding: utf-8 -*-\nimport os\n";
+        assert_eq!(
+            annotate(text, python, "MIT", None),
+            Change::Added(
+                "#!/usr/bin/env python\n# -*- coding: utf-8 -*-\n# SPDX-License-Identifier: MIT\n\nimport os\n"
+                    .into()
+            )
+        );
+        let xml = Lang::Xml.comment_style();
+        assert_eq!(
+            annotate("<?xml version=\"1.0\"?>", xml, "MIT", None),
+            Change::Added(
+                "<?xml version=\"1.0\"?>\n<!--\nSPDX-License-Identifier: MIT\n-->\n".into()
+            )
+        );
+    }
+
+    #[test]
+    fn test_update() {
+        let rust = Lang::Rust.comment_style();
+        let text = "// Copyright (c) 2020 Nyakku Shigure\n// SPDX-License-Identifier: MIT\n\nfn main() {}\n";
+        assert_eq!(annotate(text, rust, "MIT", None), Change::Unchanged);
+        assert_eq!(
+            annotate(text, rust, "MIT OR Apache-2.0", COPYRIGHT),
+            Change::Updated(
+                "// Copyright (c) 2023 Nyakku Shigure\n// SPDX-License-Identifier: MIT OR Apache-2.0\n\nfn main() {}\n"
+                    .into()
+            )
+        );
+
+        let c = Lang::C.comment_style();
+        let text = "/* SPDX-License-Identifier: GPL-2.0 */\nint main;\n";
+        assert_eq!(
+            annotate(text, c, "GPL-2.0-only", COPYRIGHT),
+            Change::Updated(
+                "/* Copyright (c) 2023 Nyakku Shigure */\n/* SPDX-License-Identifier: GPL-2.0-only */\nint main;\n"
+                    .into()
+            )
+        );
+
+        let text =
+            "# SPDX-FileCopyrightText: 2020 Nyakku Shigure\n# SPDX-License-Identifier: MIT\n";
+        let python = Lang::Python.comment_style();
+        assert_eq!(annotate(text, python, "MIT", COPYRIGHT), Change::Unchanged);
+    }
+
+    #[test]
+    fn test_identifier() {
+        let text = "/* SPDX-License-Identifier: GPL-2.0 */\r\nint main;\r\n";
+        assert_eq!(identifier(text), Some(String::from("GPL-2.0")));
+        assert_eq!(
+            replace_identifier(text, "GPL-2.0-only"),
+            Some("/* SPDX-License-Identifier: GPL-2.0-only */\r\nint main;\r\n".into())
+        );
+        let text = "// SPDX-License-Identifier: MIT\nfn main() {}\n";
+        assert_eq!(identifier(text), Some(String::from("MIT")));
+        assert_eq!(identifier("fn main() {}\n"), None);
+        assert_eq!(replace_identifier("fn main() {}\n", "MIT"), None);
+    }
+
+    #[test]
+    fn test_identifier_in_string() {
+        let text = "pub const IDENTIFIER: &str = \"SPDX-License-Identifier:\";\n";
+        assert_eq!(identifier(text), None);
+        assert_eq!(replace_identifier(text, "MIT"), None);
+        let rust = Lang::Rust.comment_style();
+        assert_eq!(
+            annotate(text, rust, "MIT", None),
+            Change::Added(format!("// SPDX-License-Identifier: MIT\n\n{text}"))
+        );
+        let text = "x = 1\nprint(\"SPDX-License-Identifier: MIT\")\n";
+        assert_eq!(identifier(text), None);
+    }
+}
@@ -1,11 +1,48 @@
-use clap::{Parser, Subcommand};
+use crate::comment::Lang;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde::Deserialize;
+use std::path::PathBuf;
+
+#[cfg(feature = "bundled")]
+const VERSION: &str = concat!(
+    env!("CARGO_PKG_VERSION"),
+    " (bundled SPDX license list ",
+    env!("LIC_BUNDLED_LIST_VERSION"),
+    ")"
+);
+#[cfg(not(feature = "bundled"))]
+const VERSION: &str = env!("CARGO_PKG_VERSION");
 
 #[derive(Parser, Debug, PartialEq)]
-#[command(author, version, about, long_about = None)]
+#[command(author, version = VERSION, about, long_about = None)]
 #[command(allow_negative_numbers = true)]
 pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
+
+    /// Never access the network, only use the cached license data
+    #[arg(long, global = true)]
+    pub offline: bool,
+
+    /// Download the license list again even if a cached or bundled one is available
+    #[arg(long, global = true, conflicts_with = "offline")]
+    pub refresh: bool,
+
+    /// Pin a tagged release of the SPDX license list, e.g. 3.21
+    #[arg(long, global = true, value_name = "VERSION")]
+    pub list_version: Option<String>,
+
+    /// Where to cache the downloaded license data [default: $XDG_CACHE_HOME/lic]
+    #[arg(long, global = true, value_name = "PATH")]
+    pub cache_dir: Option<PathBuf>,
+
+    /// Base URL of a spdx/license-list-data mirror, pointing to its `json` directory
+    #[arg(long, global = true, value_name = "URL")]
+    pub mirror: Option<String>,
+
+    /// Local checkout of spdx/license-list-data, pointing to its `json` directory
+    #[arg(long, global = true, value_name = "PATH", conflicts_with = "mirror")]
+    pub data_dir: Option<PathBuf>,
 }
 
 #[derive(Subcommand, Debug, PartialEq)]
@@ -13,23 +50,122 @@ pub enum Commands {
     New(OptionsNew),
     Auto(OptionsAuto),
     Search(OptionsSearch),
+    Header(OptionsHeader),
+    Annotate(OptionsAnnotate),
+    Detect(OptionsDetect),
+    Check(OptionsCheck),
+    Fix(OptionsFix),
+    Set(OptionsSet),
+    Relicense(OptionsRelicense),
+}
+
+/// How the license files are named when writing them.
+#[derive(ValueEnum, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Naming {
+    /// `LICENSE` for a single license, `LICENSE-<ID>` for several
+    #[default]
+    Auto,
+    /// All the licenses in a single `LICENSE` file
+    Single,
+    /// `LICENSE-<ID>` for every license, e.g. `LICENSE-MIT` and `LICENSE-APACHE`
+    Suffix,
+    /// `LICENSES/<id>.txt` for every license and exception, as REUSE expects
+    Reuse,
+}
+
+/// The package manager whose manifest declares the license.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Ecosystem {
+    /// `Cargo.toml`
+    Rust,
+    /// `pyproject.toml`
+    Python,
+    /// `package.json`
+    Node,
+}
+
+/// Output format of a report.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum Format {
+    /// Human-readable text
+    #[default]
+    Text,
+    /// JSON, for scripts and CI
+    Json,
+}
+
+/// Copyright of the license texts and headers.
+#[derive(Args, Debug, Default, PartialEq)]
+pub struct CopyrightArgs {
+    /// Copyright holder to fill in
+    #[arg(long, value_name = "NAME")]
+    pub holder: Option<String>,
+
+    /// Copyright year to fill in, e.g. 2023 or 2020-2023
+    #[arg(long)]
+    pub year: Option<String>,
+
+    /// Take the copyright holder and years from the local git repository
+    #[arg(long)]
+    pub from_git: bool,
+}
+
+/// How the license files are written.
+#[derive(Args, Debug, Default, PartialEq)]
+pub struct LicenseFileArgs {
+    #[arg(short, long)]
+    pub width: Option<usize>,
+
+    /// Naming scheme of the written license files [default: auto]
+    #[arg(long, value_enum)]
+    pub naming: Option<Naming>,
+
+    /// Overwrite existing license files
+    #[arg(short, long)]
+    pub force: bool,
 }
 
+/// Help of `--holder` for the commands filling in the manifest authors.
+const HOLDER_FROM_AUTHORS: &str = "Copyright holder to fill in [default: the manifest authors]";
+
 #[derive(Parser, Debug, PartialEq)]
+#[command(mut_arg("naming", |arg| arg.requires("write")))]
+#[command(mut_arg("force", |arg| arg.requires("write")))]
 pub struct OptionsNew {
     pub id: String,
 
-    #[arg(short, long)]
-    pub width: Option<usize>,
+    /// Write the license files instead of printing the licenses
+    #[arg(long)]
+    pub write: bool,
+
+    #[command(flatten)]
+    pub files: LicenseFileArgs,
+
+    #[command(flatten)]
+    pub copyright: CopyrightArgs,
 }
 
 #[derive(Parser, Debug, PartialEq)]
+#[command(mut_arg("holder", |arg| arg.help(HOLDER_FROM_AUTHORS)))]
 pub struct OptionsAuto {
-    #[arg(short, long)]
-    pub width: Option<usize>,
+    #[command(flatten)]
+    pub files: LicenseFileArgs,
 
-    #[arg(short, long)]
-    pub force: bool,
+    #[command(flatten)]
+    pub copyright: CopyrightArgs,
+
+    /// Write the license files of every package of the Cargo or npm workspace
+    #[arg(long, conflicts_with = "manifest_path")]
+    pub workspace: bool,
+
+    /// Path to the manifest to read, e.g. `crates/foo/Cargo.toml`
+    #[arg(long, value_name = "PATH", conflicts_with = "ecosystem")]
+    pub manifest_path: Option<PathBuf>,
+
+    /// Read the manifest of this ecosystem only [default: all of them, which must agree]
+    #[arg(long, value_enum)]
+    pub ecosystem: Option<Ecosystem>,
 }
 
 #[derive(Parser, Debug, PartialEq)]
@@ -38,6 +174,126 @@ pub struct OptionsSearch {
 
     #[arg(short, long, default_value_t = 20)]
     pub number: usize,
+
+    /// Search the license exceptions instead of the licenses
+    #[arg(short, long)]
+    pub exceptions: bool,
+}
+
+#[derive(Parser, Debug, PartialEq)]
+pub struct OptionsHeader {
+    pub id: String,
+
+    /// Language of the source file, which decides the comment syntax
+    #[arg(short, long, value_enum)]
+    pub lang: Lang,
+
+    /// Use the standard header of the license (e.g. the GPL or Apache boilerplate)
+    /// instead of a `SPDX-License-Identifier` line
+    #[arg(long)]
+    pub standard: bool,
+
+    #[arg(short, long, default_value_t = 80)]
+    pub width: usize,
+
+    #[command(flatten)]
+    pub copyright: CopyrightArgs,
+}
+
+#[derive(Parser, Debug, PartialEq)]
+pub struct OptionsAnnotate {
+    /// Files or directories to annotate, directories are walked respecting `.gitignore`
+    #[arg(default_value = ".")]
+    pub paths: Vec<PathBuf>,
+
+    /// SPDX license expression of the files
+    #[arg(short, long, value_name = "EXPRESSION")]
+    pub license: String,
+
+    #[command(flatten)]
+    pub copyright: CopyrightArgs,
+
+    /// Only report what would be changed
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Parser, Debug, PartialEq)]
+pub struct OptionsDetect {
+    /// License file to identify, e.g. LICENSE or COPYING
+    pub file: PathBuf,
+
+    /// Number of closest licenses listed when nothing matches exactly
+    #[arg(short, long, default_value_t = 5)]
+    pub number: usize,
+}
+
+#[derive(Parser, Debug, PartialEq)]
+pub struct OptionsCheck {
+    /// Output format of the report
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    pub format: Format,
+
+    /// Check every package of the Cargo or npm workspace
+    #[arg(long)]
+    pub workspace: bool,
+}
+
+#[derive(Parser, Debug, PartialEq)]
+pub struct OptionsFix {
+    /// Only report what would be changed
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Parser, Debug, PartialEq)]
+#[command(mut_arg("holder", |arg| arg.help(HOLDER_FROM_AUTHORS).requires("write")))]
+#[command(mut_arg("year", |arg| arg.requires("write")))]
+#[command(mut_arg("from_git", |arg| arg.requires("write")))]
+#[command(mut_arg("width", |arg| arg.requires("write")))]
+#[command(mut_arg("naming", |arg| arg.requires("write")))]
+#[command(mut_arg("force", |arg| arg.requires("write")))]
+pub struct OptionsSet {
+    /// SPDX license expression to write into the manifests
+    pub id: String,
+
+    /// Only report what would be changed
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Also write the license files, as `lic auto` does
+    #[arg(long, conflicts_with = "dry_run")]
+    pub write: bool,
+
+    #[command(flatten)]
+    pub files: LicenseFileArgs,
+
+    #[command(flatten)]
+    pub copyright: CopyrightArgs,
+}
+
+#[derive(Parser, Debug, PartialEq)]
+#[command(mut_arg("holder", |arg| arg.help(HOLDER_FROM_AUTHORS)))]
+pub struct OptionsRelicense {
+    /// SPDX license expression the project is currently under
+    pub from: String,
+
+    /// SPDX license expression the project moves to
+    pub to: String,
+
+    /// Only print the plan
+    #[arg(long)]
+    pub dry_run: bool,
+
+    #[arg(short, long)]
+    pub width: Option<usize>,
+
+    /// Naming scheme of the new license files [default: auto]
+    #[arg(long, value_enum)]
+    pub naming: Option<Naming>,
+
+    #[command(flatten)]
+    pub copyright: CopyrightArgs,
 }
 
 #[cfg(test)]
@@ -51,9 +307,41 @@ mod tests {
             cli.command,
             Commands::New(OptionsNew {
                 id: "MIT".into(),
-                width: None
+                write: false,
+                files: LicenseFileArgs::default(),
+                copyright: CopyrightArgs::default(),
+            })
+        );
+        let cli = Cli::parse_from([
+            "lic",
+            "new",
+            "MIT OR Apache-2.0",
+            "--write",
+            "--naming",
+            "reuse",
+            "--holder",
+            "Nyakku Shigure",
+            "--year",
+            "2023",
+        ]);
+        assert_eq!(
+            cli.command,
+            Commands::New(OptionsNew {
+                id: "MIT OR Apache-2.0".into(),
+                write: true,
+                files: LicenseFileArgs {
+                    width: None,
+                    naming: Some(Naming::Reuse),
+                    force: false,
+                },
+                copyright: CopyrightArgs {
+                    holder: Some("Nyakku Shigure".into()),
+                    year: Some("2023".into()),
+                    from_git: false,
+                },
             })
         );
+        assert!(Cli::try_parse_from(["lic", "new", "MIT", "--force"]).is_err());
     }
 
     #[test]
@@ -62,10 +350,86 @@ mod tests {
         assert_eq!(
             cli.command,
             Commands::Auto(OptionsAuto {
-                width: Some(80),
-                force: false
+                files: LicenseFileArgs {
+                    width: Some(80),
+                    naming: None,
+                    force: false,
+                },
+                copyright: CopyrightArgs::default(),
+                workspace: false,
+                manifest_path: None,
+                ecosystem: None,
             })
         );
+        let cli = Cli::parse_from(["lic", "auto", "--ecosystem", "python"]);
+        assert!(matches!(
+            cli.command,
+            Commands::Auto(OptionsAuto {
+                ecosystem: Some(Ecosystem::Python),
+                ..
+            })
+        ));
+        let cli = Cli::try_parse_from([
+            "lic",
+            "auto",
+            "--manifest-path",
+            "Cargo.toml",
+            "--ecosystem",
+            "node",
+        ]);
+        assert!(cli.is_err());
+        let cli = Cli::parse_from(["lic", "auto", "--workspace"]);
+        assert!(matches!(
+            cli.command,
+            Commands::Auto(OptionsAuto {
+                workspace: true,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_offline() {
+        let cli = Cli::parse_from(["lic", "new", "MIT", "--offline"]);
+        assert!(cli.offline);
+        let cli = Cli::parse_from(["lic", "new", "MIT"]);
+        assert!(!cli.offline);
+    }
+
+    #[test]
+    fn test_data_source() {
+        let cli = Cli::parse_from([
+            "lic",
+            "search",
+            "gpl",
+            "--mirror",
+            "https://example.com/json",
+        ]);
+        assert_eq!(cli.mirror, Some("https://example.com/json".into()));
+        let cli = Cli::parse_from([
+            "lic",
+            "--data-dir",
+            "license-list-data/json",
+            "search",
+            "gpl",
+        ]);
+        assert_eq!(cli.data_dir, Some("license-list-data/json".into()));
+        assert!(Cli::try_parse_from([
+            "lic",
+            "search",
+            "gpl",
+            "--mirror",
+            "https://example.com/json",
+            "--data-dir",
+            "json"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_list_version() {
+        let cli = Cli::parse_from(["lic", "new", "Apache-2.0", "--list-version", "3.21"]);
+        assert_eq!(cli.list_version, Some("3.21".into()));
     }
 
     #[test]
@@ -75,7 +439,123 @@ mod tests {
             cli.command,
             Commands::Search(OptionsSearch {
                 id: String::from("gpl"),
-                number: 50
+                number: 50,
+                exceptions: false,
+            })
+        );
+        let cli = Cli::parse_from(["lic", "search", "llvm", "--exceptions"]);
+        assert_eq!(
+            cli.command,
+            Commands::Search(OptionsSearch {
+                id: String::from("llvm"),
+                number: 20,
+                exceptions: true,
+            })
+        );
+    }
+
+    #[test]
+    fn test_header() {
+        let cli = Cli::parse_from(["lic", "header", "MIT", "--lang", "rs", "--standard"]);
+        assert_eq!(
+            cli.command,
+            Commands::Header(OptionsHeader {
+                id: "MIT".into(),
+                lang: Lang::Rust,
+                standard: true,
+                width: 80,
+                copyright: CopyrightArgs::default(),
+            })
+        );
+        assert!(Cli::try_parse_from(["lic", "header", "MIT"]).is_err());
+    }
+
+    #[test]
+    fn test_annotate() {
+        let cli = Cli::parse_from(["lic", "annotate", "-l", "MIT", "--dry-run"]);
+        assert_eq!(
+            cli.command,
+            Commands::Annotate(OptionsAnnotate {
+                paths: vec![PathBuf::from(".")],
+                license: "MIT".into(),
+                copyright: CopyrightArgs::default(),
+                dry_run: true,
+            })
+        );
+    }
+
+    #[test]
+    fn test_detect() {
+        let cli = Cli::parse_from(["lic", "detect", "COPYING"]);
+        assert_eq!(
+            cli.command,
+            Commands::Detect(OptionsDetect {
+                file: PathBuf::from("COPYING"),
+                number: 5,
+            })
+        );
+    }
+
+    #[test]
+    fn test_check() {
+        let cli = Cli::parse_from(["lic", "check"]);
+        assert_eq!(
+            cli.command,
+            Commands::Check(OptionsCheck {
+                format: Format::Text,
+                workspace: false,
+            })
+        );
+        let cli = Cli::parse_from(["lic", "check", "--format", "json", "--workspace"]);
+        assert_eq!(
+            cli.command,
+            Commands::Check(OptionsCheck {
+                format: Format::Json,
+                workspace: true,
+            })
+        );
+    }
+
+    #[test]
+    fn test_fix() {
+        let cli = Cli::parse_from(["lic", "fix", "--dry-run"]);
+        assert_eq!(cli.command, Commands::Fix(OptionsFix { dry_run: true }));
+    }
+
+    #[test]
+    fn test_set() {
+        let cli = Cli::parse_from(["lic", "set", "MIT OR Apache-2.0", "--write", "-f"]);
+        assert_eq!(
+            cli.command,
+            Commands::Set(OptionsSet {
+                id: String::from("MIT OR Apache-2.0"),
+                dry_run: false,
+                write: true,
+                files: LicenseFileArgs {
+                    width: None,
+                    naming: None,
+                    force: true,
+                },
+                copyright: CopyrightArgs::default(),
+            })
+        );
+        assert!(Cli::try_parse_from(["lic", "set", "MIT", "--force"]).is_err());
+        assert!(Cli::try_parse_from(["lic", "set", "MIT", "--holder", "lic"]).is_err());
+        assert!(Cli::try_parse_from(["lic", "set", "MIT", "--write", "--dry-run"]).is_err());
+    }
+
+    #[test]
+    fn test_relicense() {
+        let cli = Cli::parse_from(["lic", "relicense", "MIT", "MIT OR Apache-2.0", "--dry-run"]);
+        assert_eq!(
+            cli.command,
+            Commands::Relicense(OptionsRelicense {
+                from: String::from("MIT"),
+                to: String::from("MIT OR Apache-2.0"),
+                dry_run: true,
+                width: None,
+                naming: None,
+                copyright: CopyrightArgs::default(),
             })
         );
     }
@@ -0,0 +1,100 @@
+use super::{badge_warning, copyright, load_licenses, parse_expression};
+use crate::annotate::{annotate, Change};
+use crate::cli::OptionsAnnotate;
+use crate::comment::Lang;
+use crate::config::Config;
+use crate::spdx::source::LicenseSource;
+use colored::*;
+use ignore::WalkBuilder;
+use std::error::Error;
+use std::fs;
+
+pub async fn run(
+    options: OptionsAnnotate,
+    config: &Config,
+    source: &dyn LicenseSource,
+) -> Result<(), Box<dyn Error>> {
+    let licenses = load_licenses(source).await?;
+    let Some(expression) = parse_expression(&options.license, &licenses, source).await? else {
+        return Err(format!("{} is not a valid license expression.", options.license).into());
+    };
+    let expression = expression.to_string();
+    let copyright = copyright(&options.copyright, &[], config).line();
+
+    let (would, verb_added, verb_updated) = if options.dry_run {
+        ("Would ", "add", "update")
+    } else {
+        ("", "Added", "Updated")
+    };
+    let (mut added, mut updated, mut unchanged, mut skipped) = (0, 0, 0, 0);
+    let mut paths = options.paths.iter();
+    let mut walk = WalkBuilder::new(paths.next().unwrap());
+    for path in paths {
+        walk.add(path);
+    }
+    for entry in walk.build() {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                eprintln!("{} {e}", badge_warning());
+                skipped += 1;
+                continue;
+            }
+        };
+        if !entry
+            .file_type()
+            .is_some_and(|file_type| file_type.is_file())
+        {
+            continue;
+        }
+        let path = entry.path();
+        // Binary files are not valid UTF-8 and are skipped as well.
+        let Ok(text) = fs::read_to_string(path) else {
+            println!(
+                "{:>9} {} (not a text file)",
+                "Skipped".yellow(),
+                path.display()
+            );
+            skipped += 1;
+            continue;
+        };
+        let Some(lang) = Lang::detect(path, text.lines().next().unwrap_or_default()) else {
+            println!(
+                "{:>9} {} (unknown language)",
+                "Skipped".yellow(),
+                path.display()
+            );
+            skipped += 1;
+            continue;
+        };
+        let (verb, text) = match annotate(
+            &text,
+            lang.comment_style(),
+            &expression,
+            copyright.as_deref(),
+        ) {
+            Change::Added(text) => {
+                added += 1;
+                (verb_added, text)
+            }
+            Change::Updated(text) => {
+                updated += 1;
+                (verb_updated, text)
+            }
+            Change::Unchanged => {
+                unchanged += 1;
+                continue;
+            }
+        };
+        let verb = format!("{would}{verb}");
+        println!("{:>9} {}", verb.green().bold(), path.display());
+        if !options.dry_run {
+            fs::write(path, text)?;
+        }
+    }
+    println!(
+        "{}{added} added, {updated} updated, {unchanged} unchanged, {skipped} skipped",
+        if options.dry_run { "(dry run) " } else { "" }
+    );
+    Ok(())
+}
@@ -0,0 +1,174 @@
+use super::detect::{identify_common, Identification};
+use super::files::{license_files, license_globs, write_license_files, LicenseFile};
+use super::{
+    badge_warning, display_dir, ecosystem_of, find_workspace, license_texts, load_licenses,
+    manifest_dir, parse_expression, read_manifest, ManifestInfo,
+};
+use crate::cli::{CopyrightArgs, LicenseFileArgs, Naming, OptionsAuto};
+use crate::config::Config;
+use crate::manifest::ManifestError;
+use crate::spdx::list::Licenses;
+use crate::spdx::matching::normalize;
+use crate::spdx::source::LicenseSource;
+use colored::*;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+/// Reports the license of the file a manifest points to instead of declaring
+/// a license id, the file being kept as is.
+async fn report_license_file(
+    path: &Path,
+    filename: &str,
+    licenses: &Licenses,
+    source: &dyn LicenseSource,
+) -> Result<(), Box<dyn Error>> {
+    if !path.is_file() {
+        return Err(format!(
+            "The license file {} of {filename} does not exist.",
+            path.display()
+        )
+        .into());
+    }
+    let text =
+        fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {e}", path.display()))?;
+    let Identification { exact, closest } =
+        identify_common(&normalize(&text), licenses, source, 1).await;
+    match (exact.first(), closest.first()) {
+        (Some(lic), _) => println!(
+            "{:>9} {} ({}), the license file of {filename}",
+            "Found".green().bold(),
+            path.display(),
+            lic.license_id
+        ),
+        (None, Some((score, lic))) => eprintln!(
+            "{} {} is not a common license text, the closest one is {} ({:.1}%). Use `lic detect` to compare it with every license.",
+            badge_warning(),
+            path.display(),
+            lic.license_id.green(),
+            score * 100.0
+        ),
+        (None, None) => eprintln!(
+            "{} {} is not a common license text. Use `lic detect` to compare it with every license.",
+            badge_warning(),
+            path.display()
+        ),
+    }
+    Ok(())
+}
+
+/// Writes the license files of the manifest in `dir`.
+pub async fn auto(
+    dir: &Path,
+    manifest: ManifestInfo,
+    files: &LicenseFileArgs,
+    copyright: &CopyrightArgs,
+    config: &Config,
+    licenses: &Licenses,
+    source: &dyn LicenseSource,
+) -> Result<(), Box<dyn Error>> {
+    let copyright = super::copyright(copyright, &manifest.authors, config);
+    let Some(license) = &manifest.license else {
+        let path = dir.join(manifest.license_file.as_ref().unwrap());
+        return report_license_file(&path, &manifest.filename, licenses, source).await;
+    };
+    let Some(expression) = parse_expression(license, licenses, source).await? else {
+        return Err(format!("{license} is not a valid license expression.").into());
+    };
+    let texts = license_texts(&expression, licenses, source, &copyright, files.width).await?;
+    let globs = match &manifest.license_files {
+        Some(patterns) => Some(license_globs(patterns)?),
+        None => None,
+    };
+    let is_packaged = |file: &LicenseFile| {
+        globs
+            .as_ref()
+            .map_or(true, |globs| globs.is_match(&file.path))
+    };
+    // Without a scheme chosen, pick one the manifest packages.
+    let naming = files.naming.or(config.naming).unwrap_or_else(|| {
+        [Naming::Auto, Naming::Reuse, Naming::Suffix, Naming::Single]
+            .into_iter()
+            .find(|naming| license_files(&texts, *naming).iter().all(is_packaged))
+            .unwrap_or_default()
+    });
+    let force = files.force;
+    let files = license_files(&texts, naming);
+    write_license_files(dir, &files, force)?;
+    for file in files.iter().filter(|file| !is_packaged(file)) {
+        eprintln!(
+            "{} {} is not matched by the license files of {}.",
+            badge_warning(),
+            file.path.display(),
+            manifest.filename
+        );
+    }
+    Ok(())
+}
+
+pub async fn run(
+    options: OptionsAuto,
+    config: &Config,
+    source: &dyn LicenseSource,
+) -> Result<(), Box<dyn Error>> {
+    if options.workspace {
+        let licenses = load_licenses(source).await?;
+        for dir in find_workspace()? {
+            match read_manifest(&dir, options.ecosystem) {
+                Ok(manifest) => {
+                    auto(
+                        &dir,
+                        manifest,
+                        &options.files,
+                        &options.copyright,
+                        config,
+                        &licenses,
+                        source,
+                    )
+                    .await?
+                }
+                Err(e @ ManifestError::Unlicensed) => {
+                    eprintln!("{} {}: {e}", badge_warning(), display_dir(&dir))
+                }
+                Err(e) => return Err(format!("{}: {e}", display_dir(&dir)).into()),
+            }
+        }
+        return Ok(());
+    }
+
+    let (dir, ecosystem) = match &options.manifest_path {
+        Some(path) => {
+            let ecosystem = ecosystem_of(path)
+                .ok_or_else(|| format!("{} is not a supported manifest file.", path.display()))?;
+            let dir = path.parent().unwrap_or(Path::new("")).to_path_buf();
+            (Ok(dir), Some(ecosystem))
+        }
+        None => (manifest_dir(options.ecosystem), options.ecosystem),
+    };
+    match dir.and_then(|dir| Ok((read_manifest(&dir, ecosystem)?, dir))) {
+        Ok((manifest, dir)) => {
+            let licenses = load_licenses(source).await?;
+            auto(
+                &dir,
+                manifest,
+                &options.files,
+                &options.copyright,
+                config,
+                &licenses,
+                source,
+            )
+            .await?;
+        }
+        Err(e @ ManifestError::Unlicensed) => eprintln!("{} {e}", badge_warning()),
+        Err(e @ ManifestError::Conflict(_)) => {
+            return Err(format!(
+                "{e} Use {} or {} to pick one of them.",
+                "--ecosystem".blue(),
+                "--manifest-path".blue()
+            )
+            .into())
+        }
+        Err(e) => return Err(e.into()),
+    }
+    Ok(())
+}
@@ -0,0 +1,367 @@
+use super::detect::identify_common;
+use super::files::{find_license_files, glob_license_files, is_license_file_name};
+use super::{
+    badge_error, display_dir, find_workspace, load_exceptions, load_licenses, manifest_dir,
+    normalize_expression, read_manifest,
+};
+use crate::cli::{Format, OptionsCheck};
+use crate::manifest::{ManifestError, UNLICENSED};
+use crate::spdx::exceptions::Exceptions;
+use crate::spdx::expression::{Expression, LicenseId, ValidationErrorKind};
+use crate::spdx::matching::normalize;
+use crate::spdx::source::LicenseSource;
+use colored::*;
+use serde::Serialize;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize, Default)]
+struct Report {
+    /// Directory of the workspace package, for `--workspace`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dir: Option<PathBuf>,
+    ok: bool,
+    /// License expression declared in the manifest
+    license: Option<String>,
+    files: Vec<FileReport>,
+    problems: Vec<Problem>,
+}
+
+#[derive(Serialize)]
+struct FileReport {
+    path: PathBuf,
+    /// Declared license and exception ids whose text is in the file
+    ids: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+enum Problem {
+    /// The manifest is missing or declares no license
+    Manifest {
+        message: String,
+    },
+    InvalidExpression {
+        message: String,
+    },
+    UnknownId {
+        id: String,
+        suggestions: Vec<String>,
+    },
+    /// No license file has the text of a declared license or exception
+    MissingFile {
+        id: String,
+    },
+    /// A license file has none of the declared licenses, `detected` are the
+    /// licenses it matches instead
+    Mismatch {
+        path: PathBuf,
+        detected: Vec<String>,
+    },
+    /// A license file pattern of the manifest matches no file
+    UnmatchedPattern {
+        pattern: String,
+    },
+    /// The license file the manifest refers to does not exist
+    LicenseFileNotFound {
+        path: PathBuf,
+    },
+    /// The license file the manifest refers to matches none of the common
+    /// licenses
+    UnknownLicenseText {
+        path: PathBuf,
+    },
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::Manifest { message } => write!(f, "{message}"),
+            Problem::InvalidExpression { message } => {
+                write!(f, "Invalid license expression: {message}.")
+            }
+            Problem::UnknownId { id, suggestions } => {
+                write!(f, "Unknown license id: {}.", id.blue().bold())?;
+                if !suggestions.is_empty() {
+                    write!(f, " Did you mean {}?", suggestions.join(", "))?;
+                }
+                Ok(())
+            }
+            Problem::MissingFile { id } => {
+                write!(f, "No license file contains the text of {}.", id.blue())
+            }
+            Problem::Mismatch { path, detected } => {
+                write!(
+                    f,
+                    "{} contains none of the declared licenses",
+                    path.display()
+                )?;
+                if detected.is_empty() {
+                    write!(f, ".")
+                } else {
+                    write!(f, ", it is {}.", detected.join(", ").yellow())
+                }
+            }
+            Problem::UnmatchedPattern { pattern } => {
+                write!(
+                    f,
+                    "The license file pattern {} matches no file.",
+                    pattern.blue()
+                )
+            }
+            Problem::LicenseFileNotFound { path } => {
+                write!(
+                    f,
+                    "The license file {} of the manifest does not exist.",
+                    path.display()
+                )
+            }
+            Problem::UnknownLicenseText { path } => {
+                write!(
+                    f,
+                    "{} is not a common license text, use `lic detect` to compare it with every license.",
+                    path.display()
+                )
+            }
+        }
+    }
+}
+
+impl Report {
+    fn finish(mut self) -> Report {
+        self.ok = self.problems.is_empty();
+        self
+    }
+}
+
+/// Whether a normalized text contains a license or an exception.
+type Contains = Box<dyn Fn(&str) -> bool>;
+
+/// Checks the license files of `dir` against the license of its manifest.
+async fn check(dir: &Path, source: &dyn LicenseSource) -> Result<Report, Box<dyn Error>> {
+    let mut report = Report::default();
+    let manifest = match read_manifest(dir, None) {
+        Ok(manifest) => manifest,
+        Err(ManifestError::Unlicensed) => {
+            report.license = Some(UNLICENSED.into());
+            return Ok(report.finish());
+        }
+        Err(e) => {
+            let message = e.to_string();
+            report.problems.push(Problem::Manifest { message });
+            return Ok(report.finish());
+        }
+    };
+    report.license = manifest.license.clone();
+    let licenses = load_licenses(source).await?;
+    if let Some(path) = &manifest.license_file {
+        if !dir.join(path).is_file() {
+            let path = path.clone();
+            report.problems.push(Problem::LicenseFileNotFound { path });
+            if manifest.license.is_none() {
+                return Ok(report.finish());
+            }
+        }
+    }
+    // Without a license expression, the license is whatever the file
+    // referred to by the manifest is.
+    let Some(license) = &manifest.license else {
+        let path = manifest.license_file.unwrap();
+        let text = fs::read_to_string(dir.join(&path))
+            .map_err(|e| format!("Cannot read {}: {e}", path.display()))?;
+        let exact = identify_common(&normalize(&text), &licenses, source, 0)
+            .await
+            .exact;
+        let ids: Vec<String> = exact.iter().map(|lic| lic.license_id.clone()).collect();
+        if ids.is_empty() {
+            let path = path.clone();
+            report.problems.push(Problem::UnknownLicenseText { path });
+        }
+        report.files.push(FileReport { path, ids });
+        return Ok(report.finish());
+    };
+    let license = normalize_expression(license, &licenses).unwrap_or(license.clone());
+    let expression = match Expression::parse(&license) {
+        Ok(expression) => expression,
+        Err(e) => {
+            let message = e.to_string();
+            report.problems.push(Problem::InvalidExpression { message });
+            return Ok(report.finish());
+        }
+    };
+    let exceptions = if expression.has_exceptions() {
+        load_exceptions(source).await?
+    } else {
+        Exceptions::default()
+    };
+    let expression = match expression.validate(&licenses, &exceptions) {
+        Ok(expression) => expression,
+        Err(errors) => {
+            for error in errors {
+                let (id, suggestions) = match error.kind {
+                    ValidationErrorKind::UnknownLicense(id) => {
+                        let similar = licenses.similar_licenses(&id, 3);
+                        (id, similar.into_iter().map(|lic| lic.license_id).collect())
+                    }
+                    ValidationErrorKind::UnknownException(id) => {
+                        let similar = exceptions.similar_exceptions(&id, 3);
+                        let similar = similar.into_iter();
+                        (id, similar.map(|e| e.license_exception_id).collect())
+                    }
+                };
+                report.problems.push(Problem::UnknownId { id, suggestions });
+            }
+            return Ok(report.finish());
+        }
+    };
+
+    let mut declared: Vec<(String, Contains)> = Vec::new();
+    for id in expression.license_ids() {
+        let details = licenses
+            .get_license(id)
+            .unwrap()
+            .get_details(source)
+            .await?;
+        let contains = move |text: &str| details.is_contained_in(text);
+        declared.push((id.to_string(), Box::new(contains)));
+    }
+    for item in expression.licenses() {
+        let Some(id) = &item.exception else {
+            continue;
+        };
+        if declared.iter().any(|(declared, _)| declared == id) {
+            continue;
+        }
+        let exception = exceptions.get_exception_case_insensitive(id).unwrap();
+        let details = exception.get_details(source).await?;
+        let contains = move |text: &str| details.is_contained_in(text);
+        declared.push((id.clone(), Box::new(contains)));
+    }
+    // The text of a `LicenseRef-` is up to the user, so a file matching no
+    // SPDX license may well be it.
+    let has_refs = expression
+        .licenses()
+        .iter()
+        .any(|item| matches!(item.license, LicenseId::Ref { .. }));
+
+    // The files listed in the manifest, or else the usual license files.
+    let mut paths = match &manifest.license_files {
+        Some(patterns) => {
+            let (paths, unmatched) = glob_license_files(dir, patterns)?;
+            for pattern in unmatched {
+                report.problems.push(Problem::UnmatchedPattern { pattern });
+            }
+            paths
+        }
+        None => find_license_files(dir)?,
+    };
+    if let Some(path) = &manifest.license_file {
+        if dir.join(path).is_file() && !paths.iter().any(|p| p == path) {
+            paths.push(path.clone());
+        }
+    }
+    for path in paths {
+        let text = fs::read_to_string(dir.join(&path))
+            .map_err(|e| format!("Cannot read {}: {e}", path.display()))?;
+        let text = normalize(&text);
+        let ids: Vec<String> = declared
+            .iter()
+            .filter(|(_, contains)| contains(&text))
+            .map(|(id, _)| id.clone())
+            .collect();
+        // Other files, e.g. `AUTHORS` or `NOTICE`, may be listed along with
+        // the license files.
+        let is_license_file = manifest.license_file.as_ref() == Some(&path)
+            || path.starts_with("LICENSES")
+            || path
+                .file_name()
+                .is_some_and(|name| is_license_file_name(&name.to_string_lossy()));
+        if ids.is_empty() && !has_refs && is_license_file {
+            let detected = identify_common(&text, &licenses, source, 0).await.exact;
+            let detected = detected.iter().map(|lic| lic.license_id.clone()).collect();
+            report.problems.push(Problem::Mismatch {
+                path: path.clone(),
+                detected,
+            });
+        }
+        report.files.push(FileReport { path, ids });
+    }
+    for (id, _) in &declared {
+        if !report.files.iter().any(|file| file.ids.contains(id)) {
+            let id = id.clone();
+            report.problems.push(Problem::MissingFile { id });
+        }
+    }
+    Ok(report.finish())
+}
+
+fn print_report(report: &Report, dir: &Path) {
+    for file in report.files.iter().filter(|file| !file.ids.is_empty()) {
+        println!(
+            "{:>9} {} ({})",
+            "Found".green().bold(),
+            dir.join(&file.path).display(),
+            file.ids.join(", ")
+        );
+    }
+    for problem in &report.problems {
+        println!("{} {problem}", badge_error());
+    }
+    match (report.ok, &report.license) {
+        (true, Some(license)) if license == UNLICENSED => {
+            println!(
+                "The package is {}, there is nothing to check.",
+                license.yellow()
+            )
+        }
+        (true, Some(license)) => {
+            println!("The license files match {}.", license.green().bold())
+        }
+        _ => {}
+    }
+}
+
+pub async fn run(options: OptionsCheck, source: &dyn LicenseSource) -> Result<(), Box<dyn Error>> {
+    let reports = if options.workspace {
+        let mut reports = Vec::new();
+        for dir in find_workspace()? {
+            let mut report = check(&Path::new(".").join(&dir), source).await?;
+            report.dir = Some(dir.clone());
+            reports.push((dir, report));
+        }
+        reports
+    } else {
+        // Without a manifest, `check` reports it as a problem.
+        let dir = manifest_dir(None).unwrap_or_default();
+        vec![(
+            dir.clone(),
+            check(&Path::new(".").join(&dir), source).await?,
+        )]
+    };
+    match options.format {
+        Format::Json if options.workspace => {
+            let reports: Vec<_> = reports.iter().map(|(_, report)| report).collect();
+            println!("{}", serde_json::to_string_pretty(&reports)?)
+        }
+        Format::Json => println!("{}", serde_json::to_string_pretty(&reports[0].1)?),
+        Format::Text => {
+            for (dir, report) in &reports {
+                if options.workspace {
+                    println!("{:>9} {}", "Checking".bold(), display_dir(dir));
+                }
+                print_report(report, dir);
+            }
+        }
+    }
+    let problems: usize = reports
+        .iter()
+        .map(|(_, report)| report.problems.len())
+        .sum();
+    match problems {
+        0 => Ok(()),
+        1 => Err("1 problem found.".into()),
+        n => Err(format!("{n} problems found.").into()),
+    }
+}
@@ -0,0 +1,169 @@
+use super::{badge_error, badge_warning, load_licenses};
+use crate::cli::OptionsDetect;
+use crate::spdx::list::{License, Licenses};
+use crate::spdx::matching::{normalize, similarity};
+use crate::spdx::source::LicenseSource;
+use colored::*;
+use futures::stream::{self, StreamExt};
+use std::error::Error;
+use std::fs;
+
+/// How many of the most similar licenses are checked against their template.
+const EXACT_CANDIDATES: usize = 10;
+
+/// Licenses usually found in license files, compared by the commands which
+/// run often instead of downloading the text of every license.
+const COMMON_LICENSES: &[&str] = &[
+    "0BSD",
+    "AGPL-3.0-only",
+    "AGPL-3.0-or-later",
+    "Apache-2.0",
+    "Artistic-2.0",
+    "BSD-2-Clause",
+    "BSD-3-Clause",
+    "BSL-1.0",
+    "CC0-1.0",
+    "EPL-2.0",
+    "GPL-2.0-only",
+    "GPL-2.0-or-later",
+    "GPL-3.0-only",
+    "GPL-3.0-or-later",
+    "ISC",
+    "LGPL-2.1-only",
+    "LGPL-2.1-or-later",
+    "LGPL-3.0-only",
+    "LGPL-3.0-or-later",
+    "MIT",
+    "MIT-0",
+    "MPL-2.0",
+    "Unlicense",
+    "Zlib",
+];
+
+/// Licenses whose template matches a text, and else the most similar ones
+/// with their similarity.
+pub struct Identification<'a> {
+    pub exact: Vec<&'a License>,
+    pub closest: Vec<(f64, &'a License)>,
+}
+
+/// Identifies the license of the normalized `text` among every license,
+/// keeping the `number` most similar licenses.
+pub async fn identify<'a>(
+    text: &str,
+    licenses: &'a Licenses,
+    source: &dyn LicenseSource,
+    number: usize,
+) -> Identification<'a> {
+    // Every license text is needed, they are cached after the first run.
+    let candidates = licenses
+        .licenses
+        .iter()
+        .filter(|lic| !lic.is_deprecated_license_id)
+        .collect();
+    compare(text, candidates, source, number).await
+}
+
+/// Identifies the license of the normalized `text` among the
+/// [`COMMON_LICENSES`], keeping the `number` most similar licenses.
+pub async fn identify_common<'a>(
+    text: &str,
+    licenses: &'a Licenses,
+    source: &dyn LicenseSource,
+    number: usize,
+) -> Identification<'a> {
+    let candidates = COMMON_LICENSES
+        .iter()
+        .filter_map(|id| licenses.get_license(id))
+        .collect();
+    compare(text, candidates, source, number).await
+}
+
+async fn compare<'a>(
+    text: &str,
+    candidates: Vec<&'a License>,
+    source: &dyn LicenseSource,
+    number: usize,
+) -> Identification<'a> {
+    let details: Vec<_> = stream::iter(candidates)
+        .map(|lic| async move { (lic, lic.get_details(source).await) })
+        .buffer_unordered(16)
+        .collect()
+        .await;
+
+    let mut failed = 0;
+    let mut candidates = Vec::new();
+    for (lic, details) in details {
+        match details {
+            Ok(details) => {
+                let score = similarity(text, &normalize(&details.license_text));
+                candidates.push((score, lic, details));
+            }
+            Err(_) => failed += 1,
+        }
+    }
+    if failed > 0 {
+        eprintln!(
+            "{} {failed} license texts cannot be loaded and have not been compared.",
+            badge_warning()
+        );
+    }
+    candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    let exact = candidates
+        .iter()
+        .take(EXACT_CANDIDATES)
+        .filter(|(_, _, details)| details.matches(text))
+        .map(|(_, lic, _)| *lic)
+        .collect();
+    let closest = candidates
+        .iter()
+        .take(number)
+        .filter(|(score, _, _)| *score > 0.0)
+        .map(|(score, lic, _)| (*score, *lic))
+        .collect();
+    Identification { exact, closest }
+}
+
+pub async fn run(options: OptionsDetect, source: &dyn LicenseSource) -> Result<(), Box<dyn Error>> {
+    let text = fs::read_to_string(&options.file)
+        .map_err(|e| format!("Cannot read {}: {e}", options.file.display()))?;
+    let text = normalize(&text);
+    if text.is_empty() {
+        return Err(format!("{} is empty.", options.file.display()).into());
+    }
+    let licenses = load_licenses(source).await?;
+    let Identification { exact, closest } =
+        identify(&text, &licenses, source, options.number).await;
+    if !exact.is_empty() {
+        for lic in exact {
+            println!(
+                "{} ({}), exact match",
+                lic.license_id.green().bold(),
+                lic.name.blue()
+            );
+        }
+        return Ok(());
+    }
+    if closest.is_empty() {
+        println!("{} No license looks like this text.", badge_error());
+        return Ok(());
+    }
+    println!("No exact match, the closest licenses are:");
+    let license_id_width = closest
+        .iter()
+        .map(|(_, lic)| lic.license_id.len())
+        .max()
+        .unwrap()
+        + 2;
+    let license_name_width = closest.iter().map(|(_, lic)| lic.name.len()).max().unwrap() + 2;
+    for (score, lic) in closest {
+        println!(
+            "{:license_id_width$} {:license_name_width$} {:>6.1}%",
+            lic.license_id.green(),
+            lic.name.blue(),
+            score * 100.0,
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,347 @@
+use super::{badge_warning, LicenseText};
+use crate::cli::Naming;
+use colored::*;
+use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
+use ignore::WalkBuilder;
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// A license file to write, relative to the project directory.
+#[derive(Debug, PartialEq)]
+pub struct LicenseFile {
+    pub path: PathBuf,
+    /// License and exception ids whose text is in the file
+    pub ids: Vec<String>,
+    pub text: String,
+}
+
+/// Names of license files, which may have a suffix as in `LICENSE-MIT` or
+/// `COPYING.md`.
+const LICENSE_FILE_NAMES: &[&str] = &["LICENSE", "LICENCE", "COPYING", "UNLICENSE"];
+
+/// Short name of a license for `LICENSE-<NAME>`, e.g. `APACHE` for
+/// `Apache-2.0`, dropping everything from the version on.
+fn short_name(id: &str) -> String {
+    let name = id
+        .split('-')
+        .take_while(|part| !part.starts_with(|c: char| c.is_ascii_digit()))
+        .collect::<Vec<_>>()
+        .join("-");
+    if name.is_empty() {
+        id.to_uppercase()
+    } else {
+        name.to_uppercase()
+    }
+}
+
+/// Splits the license texts of an expression into files, following `naming`.
+pub fn license_files(texts: &[LicenseText], naming: Naming) -> Vec<LicenseFile> {
+    let naming = match naming {
+        Naming::Auto if texts.len() > 1 => Naming::Suffix,
+        Naming::Auto => Naming::Single,
+        naming => naming,
+    };
+    match naming {
+        Naming::Auto | Naming::Single => {
+            if texts.is_empty() {
+                return Vec::new();
+            }
+            let ids = texts
+                .iter()
+                .flat_map(|text| {
+                    std::iter::once(text.id.clone())
+                        .chain(text.exceptions.iter().map(|(id, _)| id.clone()))
+                })
+                .collect();
+            let texts: Vec<_> = texts.iter().map(|text| text.full_text()).collect();
+            vec![LicenseFile {
+                path: PathBuf::from("LICENSE"),
+                ids,
+                text: texts.join("\n"),
+            }]
+        }
+        Naming::Suffix => texts
+            .iter()
+            .map(|text| {
+                let name = short_name(&text.id);
+                // Different versions of the same license can't share a name.
+                let ambiguous = texts
+                    .iter()
+                    .any(|other| other.id != text.id && short_name(&other.id) == name);
+                let name = if ambiguous {
+                    text.id.to_uppercase()
+                } else {
+                    name
+                };
+                LicenseFile {
+                    path: PathBuf::from(format!("LICENSE-{name}")),
+                    ids: std::iter::once(text.id.clone())
+                        .chain(text.exceptions.iter().map(|(id, _)| id.clone()))
+                        .collect(),
+                    text: text.full_text(),
+                }
+            })
+            .collect(),
+        Naming::Reuse => {
+            let mut files: Vec<LicenseFile> = Vec::new();
+            let entries = texts.iter().flat_map(|text| {
+                std::iter::once((&text.id, &text.text))
+                    .chain(text.exceptions.iter().map(|(id, text)| (id, text)))
+            });
+            for (id, text) in entries {
+                // An exception may be used with several licenses.
+                if files.iter().any(|file| file.ids[0] == *id) {
+                    continue;
+                }
+                files.push(LicenseFile {
+                    path: Path::new("LICENSES").join(format!("{id}.txt")),
+                    ids: vec![id.clone()],
+                    text: text.clone(),
+                });
+            }
+            files
+        }
+    }
+}
+
+/// Writes `files` under `dir`, keeping the existing ones unless `force`,
+/// and prints what has been done with each of them.
+pub fn write_license_files(dir: &Path, files: &[LicenseFile], force: bool) -> io::Result<()> {
+    let mut skipped = false;
+    for file in files {
+        let path = dir.join(&file.path);
+        let ids = file.ids.join(", ");
+        let existed = path.exists();
+        if existed && !force {
+            println!(
+                "{:>9} {} ({ids}), it already exists",
+                "Skipped".yellow().bold(),
+                path.display()
+            );
+            skipped = true;
+            continue;
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, &file.text)?;
+        println!(
+            "{:>9} {} ({ids})",
+            if existed { "Replaced" } else { "Created" }.green().bold(),
+            path.display()
+        );
+    }
+    if skipped {
+        println!(
+            "{} Some license files already exist, you may need the {} option to force an override.",
+            badge_warning(),
+            "--force".blue()
+        );
+    }
+    Ok(())
+}
+
+pub fn is_license_file_name(name: &str) -> bool {
+    let name = name.to_uppercase();
+    LICENSE_FILE_NAMES.iter().any(|prefix| {
+        name.strip_prefix(prefix)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(['-', '.', '_']))
+    })
+}
+
+/// Finds the license files in `dir`, including the `LICENSES` directory of
+/// REUSE, as paths relative to `dir`.
+pub fn find_license_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        if is_license_file_name(&name) && entry.file_type()?.is_file() {
+            files.push(PathBuf::from(name));
+        }
+    }
+    let reuse = Path::new("LICENSES");
+    if dir.join(reuse).is_dir() {
+        for entry in fs::read_dir(dir.join(reuse))? {
+            let entry = entry?;
+            let path = reuse.join(entry.file_name());
+            if path.extension().is_some_and(|extension| extension == "txt")
+                && entry.file_type()?.is_file()
+            {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn glob(pattern: &str) -> Result<Glob, Box<dyn Error>> {
+    GlobBuilder::new(pattern)
+        .literal_separator(true)
+        .build()
+        .map_err(|e| format!("Invalid license file pattern {pattern}: {e}").into())
+}
+
+/// Matcher of the license file `patterns` of a manifest, e.g. the
+/// `license-files` of PEP 639.
+pub fn license_globs(patterns: &[String]) -> Result<GlobSet, Box<dyn Error>> {
+    let mut globs = GlobSetBuilder::new();
+    for pattern in patterns {
+        globs.add(glob(pattern)?);
+    }
+    Ok(globs.build()?)
+}
+
+/// Finds the files of `dir` matched by `patterns`, as paths relative to
+/// `dir`, along with the patterns which match no file.
+pub fn glob_license_files(
+    dir: &Path,
+    patterns: &[String],
+) -> Result<(Vec<PathBuf>, Vec<String>), Box<dyn Error>> {
+    let globs = license_globs(patterns)?;
+    let mut matched = vec![false; patterns.len()];
+    let mut files = Vec::new();
+    for entry in WalkBuilder::new(dir).build() {
+        let entry = entry?;
+        if !entry
+            .file_type()
+            .is_some_and(|file_type| file_type.is_file())
+        {
+            continue;
+        }
+        let path = entry.path().strip_prefix(dir).unwrap_or(entry.path());
+        let indices = globs.matches(path);
+        if !indices.is_empty() {
+            for index in indices {
+                matched[index] = true;
+            }
+            files.push(path.to_path_buf());
+        }
+    }
+    files.sort();
+    let unmatched = patterns
+        .iter()
+        .zip(matched)
+        .filter(|(_, matched)| !matched)
+        .map(|(pattern, _)| pattern.clone())
+        .collect();
+    Ok((files, unmatched))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(id: &str, exceptions: &[&str]) -> LicenseText {
+        LicenseText {
+            id: id.into(),
+            text: format!("{id} text"),
+            exceptions: exceptions
+                .iter()
+                .map(|id| (id.to_string(), format!("{id} text")))
+                .collect(),
+        }
+    }
+
+    fn paths(files: &[LicenseFile]) -> Vec<String> {
+        files
+            .iter()
+            .map(|file| file.path.display().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_short_name() {
+        assert_eq!(short_name("MIT"), "MIT");
+        assert_eq!(short_name("Apache-2.0"), "APACHE");
+        assert_eq!(short_name("GPL-3.0-or-later"), "GPL");
+        assert_eq!(short_name("CC-BY-4.0"), "CC-BY");
+        assert_eq!(short_name("0BSD"), "0BSD");
+    }
+
+    #[test]
+    fn test_license_files() {
+        let texts = [text("MIT", &[]), text("Apache-2.0", &["LLVM-exception"])];
+        let files = license_files(&texts, Naming::Auto);
+        assert_eq!(paths(&files), ["LICENSE-MIT", "LICENSE-APACHE"]);
+        assert_eq!(files[1].ids, ["Apache-2.0", "LLVM-exception"]);
+        assert_eq!(files[1].text, "Apache-2.0 text\nLLVM-exception text");
+
+        let files = license_files(&texts[..1], Naming::Auto);
+        assert_eq!(paths(&files), ["LICENSE"]);
+
+        let files = license_files(&texts, Naming::Single);
+        assert_eq!(paths(&files), ["LICENSE"]);
+        assert_eq!(files[0].ids, ["MIT", "Apache-2.0", "LLVM-exception"]);
+
+        let files = license_files(&texts, Naming::Reuse);
+        assert_eq!(
+            paths(&files),
+            [
+                "LICENSES/MIT.txt",
+                "LICENSES/Apache-2.0.txt",
+                "LICENSES/LLVM-exception.txt"
+            ]
+        );
+
+        let texts = [text("GPL-2.0-only", &[]), text("GPL-3.0-only", &[])];
+        let files = license_files(&texts, Naming::Suffix);
+        assert_eq!(
+            paths(&files),
+            ["LICENSE-GPL-2.0-ONLY", "LICENSE-GPL-3.0-ONLY"]
+        );
+    }
+
+    #[test]
+    fn test_write_license_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let texts = [text("MIT", &[]), text("Apache-2.0", &[])];
+        let files = license_files(&texts, Naming::Suffix);
+        fs::write(dir.path().join("LICENSE-MIT"), "old").unwrap();
+
+        write_license_files(dir.path(), &files, false).unwrap();
+        let read = |name| fs::read_to_string(dir.path().join(name)).unwrap();
+        assert_eq!(read("LICENSE-MIT"), "old");
+        assert_eq!(read("LICENSE-APACHE"), "Apache-2.0 text");
+
+        write_license_files(dir.path(), &files, true).unwrap();
+        assert_eq!(read("LICENSE-MIT"), "MIT text");
+    }
+
+    #[test]
+    fn test_find_license_files() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["LICENSE-MIT", "copying.md", "LICENSES.md", "README.md"] {
+            fs::write(dir.path().join(name), "").unwrap();
+        }
+        fs::create_dir(dir.path().join("LICENSES")).unwrap();
+        fs::write(dir.path().join("LICENSES/MIT.txt"), "").unwrap();
+        let files = find_license_files(dir.path()).unwrap();
+        let files: Vec<_> = files
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect();
+        assert_eq!(files, ["LICENSE-MIT", "LICENSES/MIT.txt", "copying.md"]);
+    }
+
+    #[test]
+    fn test_glob_license_files() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["LICENSE", "LICENCE-MIT", "README.md"] {
+            fs::write(dir.path().join(name), "").unwrap();
+        }
+        fs::create_dir(dir.path().join("LICENSES")).unwrap();
+        fs::write(dir.path().join("LICENSES/MIT.txt"), "").unwrap();
+        let patterns = ["LICEN[CS]E*", "LICENSES/*.txt", "AUTHORS*"].map(String::from);
+        let (files, unmatched) = glob_license_files(dir.path(), &patterns).unwrap();
+        let files: Vec<_> = files
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect();
+        assert_eq!(files, ["LICENCE-MIT", "LICENSE", "LICENSES/MIT.txt"]);
+        assert_eq!(unmatched, ["AUTHORS*"]);
+    }
+}
@@ -0,0 +1,86 @@
+use super::{badge_warning, cargo_license_dir, load_licenses, manifest_dir};
+use crate::cli::OptionsFix;
+use crate::manifest::{CargoToml, Manifest, ManifestError, PackageJson, PyprojectToml};
+use crate::spdx::expression::{Expression, LicenseId};
+use crate::spdx::list::Licenses;
+use crate::spdx::source::LicenseSource;
+use colored::*;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+/// Replaces the deprecated license ids in the manifest `M` of `dir`,
+/// returning whether there is such a manifest.
+fn fix_manifest<M: Manifest>(
+    dir: &Path,
+    licenses: &Licenses,
+    dry_run: bool,
+) -> Result<bool, Box<dyn Error>> {
+    let Some(text) = M::read(dir) else {
+        return Ok(false);
+    };
+    let path = dir.join(M::filename());
+    let filename = path.display();
+    let license = match M::from_str(&text).and_then(M::license) {
+        Ok(license) => license,
+        Err(e) => {
+            eprintln!("{} {filename}: {e}", badge_warning());
+            return Ok(true);
+        }
+    };
+    let expression = match Expression::parse(&license) {
+        Ok(expression) => expression,
+        Err(e) => {
+            eprintln!(
+                "{} {filename}: Invalid license expression: {e}.",
+                badge_warning()
+            );
+            return Ok(true);
+        }
+    };
+    let fixed = expression.replace_deprecated(licenses);
+    for item in fixed.as_ref().unwrap_or(&expression).licenses() {
+        let LicenseId::Spdx(id) = &item.license else {
+            continue;
+        };
+        let plus = if item.or_later { "+" } else { "" };
+        if let Some(replacement) = licenses.replacement(&format!("{id}{plus}")) {
+            eprintln!(
+                "{} {filename}: {item} is deprecated, but replacing it with {replacement} would drop an exception.",
+                badge_warning()
+            );
+        }
+    }
+    let Some(fixed) = fixed else {
+        println!("{:>9} {filename} ({license})", "Unchanged".bold(),);
+        return Ok(true);
+    };
+    let fixed = fixed.to_string();
+    let verb = if dry_run {
+        "Would fix"
+    } else {
+        fs::write(&path, M::set_license(&text, &fixed)?)?;
+        "Fixed"
+    };
+    println!(
+        "{:>9} {filename} ({} -> {})",
+        verb.green().bold(),
+        license.yellow(),
+        fixed.green()
+    );
+    Ok(true)
+}
+
+pub async fn run(options: OptionsFix, source: &dyn LicenseSource) -> Result<(), Box<dyn Error>> {
+    let dir = manifest_dir(None)?;
+    let licenses = load_licenses(source).await?;
+    let found = [
+        fix_manifest::<CargoToml>(&cargo_license_dir(&dir)?, &licenses, options.dry_run)?,
+        fix_manifest::<PyprojectToml>(&dir, &licenses, options.dry_run)?,
+        fix_manifest::<PackageJson>(&dir, &licenses, options.dry_run)?,
+    ];
+    if !found.contains(&true) {
+        return Err(ManifestError::FileNotFound.into());
+    }
+    Ok(())
+}
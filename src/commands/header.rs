@@ -0,0 +1,96 @@
+use super::{badge_warning, copyright, load_licenses, parse_expression};
+use crate::cli::OptionsHeader;
+use crate::config::Config;
+use crate::spdx::expression::Expression;
+use crate::spdx::list::Licenses;
+use crate::spdx::source::LicenseSource;
+use crate::spdx::template::Copyright;
+use colored::*;
+use std::error::Error;
+
+/// The `Copyright` and `SPDX-License-Identifier` lines for `expression`.
+pub fn short_header(expression: &Expression, copyright: &Copyright) -> String {
+    let mut lines: Vec<String> = copyright.line().into_iter().collect();
+    lines.push(format!("SPDX-License-Identifier: {expression}"));
+    lines.join("\n")
+}
+
+/// The standard headers of the licenses in `expression`, or `None` (with a
+/// warning) if one of them has no standard header.
+pub async fn standard_header(
+    expression: &Expression,
+    licenses: &Licenses,
+    source: &dyn LicenseSource,
+    copyright: &Copyright,
+) -> Result<Option<String>, Box<dyn Error>> {
+    let mut headers = Vec::new();
+    for id in expression.license_ids() {
+        let details = licenses
+            .get_license(id)
+            .unwrap()
+            .get_details(source)
+            .await?;
+        match details.header_with_copyright(copyright) {
+            Some(header) => headers.push(header.trim_end().to_string()),
+            None => {
+                eprintln!(
+                    "{} {} has no standard header, using the SPDX license identifier instead.",
+                    badge_warning(),
+                    id.blue()
+                );
+                return Ok(None);
+            }
+        }
+    }
+    Ok((!headers.is_empty()).then(|| headers.join("\n\n")))
+}
+
+pub async fn run(
+    options: OptionsHeader,
+    config: &Config,
+    source: &dyn LicenseSource,
+) -> Result<(), Box<dyn Error>> {
+    let licenses = load_licenses(source).await?;
+    let Some(expression) = parse_expression(&options.id, &licenses, source).await? else {
+        return Err(format!("{} is not a valid license expression.", options.id).into());
+    };
+    let copyright = copyright(&options.copyright, &[], config);
+    let style = options.lang.comment_style();
+    let standard = if options.standard {
+        standard_header(&expression, &licenses, source, &copyright).await?
+    } else {
+        None
+    };
+    let text = match standard {
+        Some(header) => {
+            // Long URLs are kept on their own line rather than broken.
+            let width = options.width.saturating_sub(style.indent()).max(1);
+            textwrap::fill(&header, textwrap::Options::new(width).break_words(false))
+        }
+        None => short_header(&expression, &copyright),
+    };
+    print!("{}", style.comment(&text));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_header() {
+        let expression = Expression::parse("MIT OR Apache-2.0").unwrap();
+        assert_eq!(
+            short_header(&expression, &Copyright::default()),
+            "SPDX-License-Identifier: MIT OR Apache-2.0"
+        );
+        let copyright = Copyright {
+            holder: Some("Nyakku Shigure".into()),
+            year: Some("2023".into()),
+        };
+        assert_eq!(
+            short_header(&expression, &copyright),
+            "Copyright (c) 2023 Nyakku Shigure\nSPDX-License-Identifier: MIT OR Apache-2.0"
+        );
+    }
+}
@@ -0,0 +1,601 @@
+mod annotate;
+mod auto;
+mod check;
+mod detect;
+mod files;
+mod fix;
+mod header;
+mod new;
+mod relicense;
+mod search;
+mod set;
+
+use crate::cli::{Cli, Commands, CopyrightArgs, Ecosystem};
+use crate::config::Config;
+use crate::git;
+use crate::manifest::{
+    find_manifest_dir, CargoToml, Manifest, ManifestError, PackageJson, PyprojectToml, UNLICENSED,
+};
+use crate::spdx::aliases::Confidence;
+use crate::spdx::cache::Cache;
+use crate::spdx::exceptions::{get_exceptions, Exceptions};
+use crate::spdx::expression::{underline, Expression, LicenseId, ValidationErrorKind};
+use crate::spdx::list::{get_licenses, Licenses};
+use crate::spdx::source::{DirSource, HttpSource, LicenseSource};
+use crate::spdx::template::Copyright;
+use clap::ValueEnum;
+use colored::*;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+pub fn badge_error() -> ColoredString {
+    " ERROR ".black().on_red().bold()
+}
+
+pub fn badge_warning() -> ColoredString {
+    " WARN ".black().on_yellow()
+}
+
+pub async fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
+    let config = Config::load()?;
+    let source = license_source(&cli, &config)?;
+    match cli.command {
+        Commands::New(options) => new::run(options, &config, source.as_ref()).await,
+        Commands::Auto(options) => auto::run(options, &config, source.as_ref()).await,
+        Commands::Search(options) => search::run(options, source.as_ref()).await,
+        Commands::Header(options) => header::run(options, &config, source.as_ref()).await,
+        Commands::Annotate(options) => annotate::run(options, &config, source.as_ref()).await,
+        Commands::Detect(options) => detect::run(options, source.as_ref()).await,
+        Commands::Check(options) => check::run(options, source.as_ref()).await,
+        Commands::Fix(options) => fix::run(options, source.as_ref()).await,
+        Commands::Set(options) => set::run(options, &config, source.as_ref()).await,
+        Commands::Relicense(options) => relicense::run(options, &config, source.as_ref()).await,
+    }
+}
+
+fn license_source(cli: &Cli, config: &Config) -> Result<Box<dyn LicenseSource>, Box<dyn Error>> {
+    let pinned = cli
+        .list_version
+        .clone()
+        .or(config.list_version.clone())
+        .map(|version| version.trim_start_matches('v').to_string());
+    // A source chosen on the command line overrides the one from the config.
+    let (data_dir, mirror) = if cli.data_dir.is_some() || cli.mirror.is_some() {
+        (cli.data_dir.clone(), cli.mirror.clone())
+    } else {
+        (config.data_dir.clone(), config.mirror.clone())
+    };
+    if let Some(data_dir) = data_dir {
+        return Ok(Box::new(DirSource::new(data_dir).pin(pinned)));
+    }
+    let remote = match (mirror, &pinned) {
+        (Some(mirror), Some(version)) => HttpSource::mirror(&mirror.replace("{version}", version)),
+        (Some(mirror), None) if mirror.contains("{version}") => {
+            return Err(format!(
+                "The mirror {mirror} needs a pinned license list version, use {}.",
+                "--list-version".blue()
+            )
+            .into())
+        }
+        (Some(mirror), None) => HttpSource::mirror(&mirror),
+        (None, Some(version)) => HttpSource::release(version),
+        (None, None) => HttpSource::official(),
+    };
+    let cache_dir = cli
+        .cache_dir
+        .clone()
+        .or(config.cache_dir.clone())
+        .unwrap_or_else(Cache::default_dir);
+    // `--refresh` explicitly asks for the network, even if the config disables it.
+    let offline = cli.offline || (config.offline.unwrap_or(false) && !cli.refresh);
+    Ok(Box::new(
+        Cache::new(Box::new(remote))
+            .dir(cache_dir)
+            .pin(pinned)
+            .bundled(true)
+            .offline(offline)
+            .refresh(cli.refresh),
+    ))
+}
+
+/// Copyright from the git history of the current directory, if asked for
+/// with `--from-git` or the `from-git` config.
+fn git_copyright(from_git: bool, config: &Config) -> Copyright {
+    if !(from_git || config.from_git.unwrap_or(false)) {
+        return Copyright::default();
+    }
+    git::copyright(Path::new(".")).unwrap_or_else(|| {
+        eprintln!(
+            "{} Not in a git repository, cannot take the copyright from its history.",
+            badge_warning()
+        );
+        Copyright::default()
+    })
+}
+
+/// The copyright of the `--holder`, `--year` and `--from-git` options,
+/// completed by the manifest `authors` and then by the config.
+fn copyright(args: &CopyrightArgs, authors: &[String], config: &Config) -> Copyright {
+    Copyright {
+        holder: args.holder.clone(),
+        year: args.year.clone(),
+    }
+    .or(git_copyright(args.from_git, config))
+    .or(Copyright {
+        holder: (!authors.is_empty()).then(|| authors.join(", ")),
+        year: None,
+    })
+    .or(config.copyright())
+}
+
+/// What the commands need from the manifest of the current directory.
+struct ManifestInfo {
+    filename: String,
+    /// SPDX license expression, `None` if the manifest only has a license file
+    license: Option<String>,
+    /// License file the manifest points to
+    license_file: Option<PathBuf>,
+    /// Author names
+    authors: Vec<String>,
+    /// Glob patterns of the license files, if the manifest lists them
+    license_files: Option<Vec<String>>,
+}
+
+fn read<M: Manifest>(text: &str, dir: &Path) -> Result<ManifestInfo, ManifestError> {
+    let manifest = M::from_str(text)?.resolve(dir)?;
+    for warning in manifest.warnings() {
+        eprintln!("{} {warning}", badge_warning());
+    }
+    let filename = M::filename();
+    let authors = manifest.authors();
+    let license_file = manifest.license_file().map(PathBuf::from);
+    let license_files = manifest.license_files();
+    let license = match manifest.license() {
+        Ok(license) => Some(license),
+        Err(ManifestError::LicenseNotFound) if license_file.is_some() => None,
+        Err(e) => return Err(e),
+    };
+    Ok(ManifestInfo {
+        filename,
+        license,
+        license_file,
+        authors,
+        license_files,
+    })
+}
+
+fn manifest_filename(ecosystem: Ecosystem) -> String {
+    match ecosystem {
+        Ecosystem::Rust => CargoToml::filename(),
+        Ecosystem::Python => PyprojectToml::filename(),
+        Ecosystem::Node => PackageJson::filename(),
+    }
+}
+
+/// The ecosystem of a manifest path, from its file name.
+fn ecosystem_of(path: &Path) -> Option<Ecosystem> {
+    let name = path.file_name()?.to_str()?;
+    Ecosystem::value_variants()
+        .iter()
+        .copied()
+        .find(|ecosystem| manifest_filename(*ecosystem) == name)
+}
+
+/// Reads the manifest of `ecosystem` in `dir`, `None` if there is none.
+fn read_ecosystem(ecosystem: Ecosystem, dir: &Path) -> Option<Result<ManifestInfo, ManifestError>> {
+    match ecosystem {
+        Ecosystem::Rust => CargoToml::read(dir).map(|text| read::<CargoToml>(&text, dir)),
+        Ecosystem::Python => PyprojectToml::read(dir).map(|text| read::<PyprojectToml>(&text, dir)),
+        Ecosystem::Node => PackageJson::read(dir).map(|text| read::<PackageJson>(&text, dir)),
+    }
+}
+
+/// Whether two license expressions are the same, up to their formatting, the
+/// case of the ids and the order of the operands.
+fn same_license(a: &str, b: &str) -> bool {
+    match (Expression::parse(a), Expression::parse(b)) {
+        (Ok(a), Ok(b)) => a.is_equivalent(&b),
+        _ => a.trim() == b.trim(),
+    }
+}
+
+/// Reads the manifest of `ecosystem` in `dir`, or else all the manifests of
+/// `dir`, which must not declare different licenses. The first manifest
+/// declaring a license is used.
+fn read_manifest(dir: &Path, ecosystem: Option<Ecosystem>) -> Result<ManifestInfo, ManifestError> {
+    if let Some(ecosystem) = ecosystem {
+        return read_ecosystem(ecosystem, dir).unwrap_or(Err(ManifestError::FileNotFound));
+    }
+    let mut found: Vec<(String, Result<ManifestInfo, ManifestError>)> = Ecosystem::value_variants()
+        .iter()
+        .filter_map(|ecosystem| {
+            let manifest = read_ecosystem(*ecosystem, dir)?;
+            Some((manifest_filename(*ecosystem), manifest))
+        })
+        .collect();
+    let declared: Vec<(String, String)> = found
+        .iter()
+        .filter_map(|(filename, manifest)| match manifest {
+            Ok(ManifestInfo {
+                license: Some(license),
+                ..
+            }) => Some((filename.clone(), license.clone())),
+            Err(ManifestError::Unlicensed) => Some((filename.clone(), UNLICENSED.into())),
+            _ => None,
+        })
+        .collect();
+    if let Some((_, first)) = declared.first() {
+        if declared
+            .iter()
+            .any(|(_, license)| !same_license(license, first))
+        {
+            return Err(ManifestError::Conflict(declared));
+        }
+    }
+    let declares = |manifest: &Result<ManifestInfo, ManifestError>| match manifest {
+        Ok(manifest) => manifest.license.is_some() || manifest.license_file.is_some(),
+        Err(e) => *e == ManifestError::Unlicensed,
+    };
+    match found.iter().position(|(_, manifest)| declares(manifest)) {
+        Some(index) => found.swap_remove(index).1,
+        None if found.is_empty() => Err(ManifestError::FileNotFound),
+        None => found.swap_remove(0).1,
+    }
+}
+
+/// Finds the nearest directory from the current one up which has a manifest,
+/// of `ecosystem` if given.
+fn manifest_dir(ecosystem: Option<Ecosystem>) -> Result<PathBuf, ManifestError> {
+    let filenames: Vec<String> = match ecosystem {
+        Some(ecosystem) => vec![manifest_filename(ecosystem)],
+        None => Ecosystem::value_variants()
+            .iter()
+            .map(|ecosystem| manifest_filename(*ecosystem))
+            .collect(),
+    };
+    find_manifest_dir(Path::new(""), &filenames).ok_or(ManifestError::FileNotFound)
+}
+
+/// Finds the workspace of the nearest manifest, the Cargo workspace or else
+/// the npm workspaces of `package.json`, returning the directories of its
+/// packages.
+fn find_workspace() -> Result<Vec<PathBuf>, ManifestError> {
+    let dir = &manifest_dir(None)?;
+    let (root, members) = if CargoToml::read(dir).is_some() {
+        let (root, workspace) = CargoToml::find_workspace(dir)?;
+        let root = dir.join(root);
+        let members = workspace.members(&root)?;
+        (root, members)
+    } else if let Some(text) = PackageJson::read(dir) {
+        (dir.clone(), PackageJson::from_str(&text)?.members(dir)?)
+    } else {
+        return Err(ManifestError::FileNotFound);
+    };
+    if members.is_empty() {
+        return Err(ManifestError::WorkspaceNotFound);
+    }
+    Ok(members.iter().map(|member| root.join(member)).collect())
+}
+
+/// The directory of the Cargo manifest declaring the license of the crate in
+/// `dir`, the workspace root if the crate inherits it.
+fn cargo_license_dir(dir: &Path) -> Result<PathBuf, ManifestError> {
+    if let Some(text) = CargoToml::read(dir) {
+        if CargoToml::from_str(&text)?.inherits_license() {
+            let (root, _) = CargoToml::find_workspace(dir)?;
+            return Ok(dir.join(root));
+        }
+    }
+    Ok(dir.to_path_buf())
+}
+
+/// A directory relative to the current one for messages, `.` if empty.
+fn display_dir(dir: &Path) -> String {
+    match dir.as_os_str().is_empty() {
+        true => String::from("."),
+        false => dir.display().to_string(),
+    }
+}
+
+/// Loads the license list on demand, so that commands which don't need it
+/// (and `--help`) keep working without network access.
+async fn load_licenses(source: &dyn LicenseSource) -> Result<Licenses, Box<dyn Error>> {
+    get_licenses(source)
+        .await
+        .map_err(|e| format!("Cannot load the SPDX license list. {e}").into())
+}
+
+async fn load_exceptions(source: &dyn LicenseSource) -> Result<Exceptions, Box<dyn Error>> {
+    get_exceptions(source)
+        .await
+        .map_err(|e| format!("Cannot load the SPDX license exception list. {e}").into())
+}
+
+fn did_you_mean<'a>(ids: impl Iterator<Item = (&'a str, bool)>) -> String {
+    let ids = ids
+        .map(|(id, deprecated)| {
+            if deprecated {
+                id.yellow().to_string()
+            } else {
+                id.green().bold().to_string()
+            }
+        })
+        .collect::<Vec<_>>();
+    format!("Did you mean {}?", ids.join(", "))
+}
+
+/// Maps the license names of `text` which are not SPDX ids (the whole text
+/// when it is no expression, e.g. `Apache License 2.0`) to SPDX ids, warning
+/// about every mapping. `None` if there is nothing to map.
+fn normalize_expression(text: &str, licenses: &Licenses) -> Option<String> {
+    let normalize = |name: &str| {
+        let normalized = licenses.normalize_name(name)?;
+        eprintln!(
+            "{} {} is not a SPDX license id, assuming {}.",
+            badge_warning(),
+            name.blue(),
+            normalized.id.green().bold()
+        );
+        if normalized.confidence == Confidence::Low {
+            let alternatives: Vec<_> = normalized
+                .alternatives
+                .iter()
+                .map(|id| id.green().to_string())
+                .collect();
+            eprintln!(
+                "{} {} is ambiguous, it may also be {}.",
+                badge_warning(),
+                name.blue(),
+                alternatives.join(", ")
+            );
+        }
+        Some(normalized.id)
+    };
+    let Ok(expression) = Expression::parse(text) else {
+        return normalize(text.trim());
+    };
+    let mut replacements = Vec::new();
+    for item in expression.licenses() {
+        let LicenseId::Spdx(id) = &item.license else {
+            continue;
+        };
+        if licenses.get_license_case_insensitive(id).is_some() {
+            continue;
+        }
+        let mut span = item.span.clone();
+        if item.or_later {
+            span.end = text[span.end..].find('+').unwrap() + span.end + 1;
+        }
+        if let Some(id) = normalize(&text[span.clone()]) {
+            replacements.push((span, id));
+        }
+    }
+    if replacements.is_empty() {
+        return None;
+    }
+    let mut normalized = text.to_string();
+    // From right to left, so that the spans of the other items stay valid.
+    for (span, id) in replacements.into_iter().rev() {
+        normalized.replace_range(span, &id);
+    }
+    Some(normalized)
+}
+
+/// Parses `text` as a SPDX license expression and checks every id in it,
+/// reporting the problems on stderr.
+async fn parse_expression(
+    text: &str,
+    licenses: &Licenses,
+    source: &dyn LicenseSource,
+) -> Result<Option<Expression>, Box<dyn Error>> {
+    let normalized = normalize_expression(text, licenses);
+    let text = normalized.as_deref().unwrap_or(text);
+    let expression = match Expression::parse(text) {
+        Ok(expression) => expression,
+        Err(e) => {
+            eprintln!("{} Invalid license expression: {e}.", badge_error());
+            eprintln!("{}", underline(text, e.position..e.position + 1));
+            return Ok(None);
+        }
+    };
+    // The exception list is only needed (and downloaded) for `WITH` expressions.
+    let exceptions = if expression.has_exceptions() {
+        load_exceptions(source).await?
+    } else {
+        Exceptions::default()
+    };
+    let errors = match expression.validate(licenses, &exceptions) {
+        Ok(expression) => return Ok(Some(expression)),
+        Err(errors) => errors,
+    };
+    for error in errors {
+        let (id, suggestions) = match &error.kind {
+            ValidationErrorKind::UnknownLicense(id) => {
+                eprintln!(
+                    "{} Unknown license id: {}.",
+                    badge_error(),
+                    id.blue().bold()
+                );
+                let similar = licenses.similar_licenses(id, 5);
+                let suggestions = did_you_mean(
+                    similar
+                        .iter()
+                        .map(|lic| (lic.license_id.as_str(), lic.is_deprecated_license_id)),
+                );
+                (id, suggestions)
+            }
+            ValidationErrorKind::UnknownException(id) => {
+                eprintln!(
+                    "{} Unknown license exception id: {}.",
+                    badge_error(),
+                    id.blue().bold()
+                );
+                let similar = exceptions.similar_exceptions(id, 5);
+                let suggestions = did_you_mean(similar.iter().map(|exception| {
+                    (
+                        exception.license_exception_id.as_str(),
+                        exception.is_deprecated_license_id,
+                    )
+                }));
+                (id, suggestions)
+            }
+        };
+        if id.len() != text.trim().len() {
+            eprintln!("{}", underline(text, error.span));
+        }
+        eprintln!("{suggestions}");
+    }
+    Ok(None)
+}
+
+/// Warns about a deprecated license id, naming its replacement if any.
+fn warn_deprecated(id: &str, or_later: bool, licenses: &Licenses) {
+    let id = if or_later {
+        format!("{id}+")
+    } else {
+        id.to_string()
+    };
+    if let Some(replacement) = licenses.replacement(&id) {
+        eprintln!(
+            "{} The license id {} has been deprecated, use {} instead.",
+            badge_warning(),
+            id.blue(),
+            replacement.green().bold()
+        );
+    } else if licenses
+        .get_license(id.trim_end_matches('+'))
+        .is_some_and(|lic| lic.is_deprecated_license_id)
+    {
+        eprintln!(
+            "{} The license id {} has been deprecated.",
+            badge_warning(),
+            id.blue()
+        );
+    }
+}
+
+/// The text of a license in an expression, followed by the texts of the
+/// exceptions it is used `WITH`.
+struct LicenseText {
+    id: String,
+    text: String,
+    exceptions: Vec<(String, String)>,
+}
+
+impl LicenseText {
+    fn full_text(&self) -> String {
+        let mut texts = vec![self.text.as_str()];
+        texts.extend(self.exceptions.iter().map(|(_, text)| text.as_str()));
+        texts.join("\n")
+    }
+}
+
+/// Fetches the text of every distinct license in `expression`, skipping
+/// `LicenseRef-` ones whose text is up to the user.
+async fn license_texts(
+    expression: &Expression,
+    licenses: &Licenses,
+    source: &dyn LicenseSource,
+    copyright: &Copyright,
+    width: Option<usize>,
+) -> Result<Vec<LicenseText>, Box<dyn Error>> {
+    let fill = |text: String| match width {
+        Some(max_width) => textwrap::fill(&text, max_width),
+        None => text,
+    };
+    let exceptions = if expression.has_exceptions() {
+        load_exceptions(source).await?
+    } else {
+        Exceptions::default()
+    };
+    let mut texts: Vec<LicenseText> = Vec::new();
+    for item in expression.licenses() {
+        match &item.license {
+            LicenseId::Ref { .. } => eprintln!(
+                "{} {} is not a SPDX license, its text has to be written by hand.",
+                badge_warning(),
+                item.license.to_string().blue()
+            ),
+            LicenseId::Spdx(id) => warn_deprecated(id, item.or_later, licenses),
+        }
+    }
+    for id in expression.license_ids() {
+        let lic = licenses.get_license(id).unwrap();
+        let text = lic
+            .get_details(source)
+            .await?
+            .text_with_copyright(copyright);
+        texts.push(LicenseText {
+            id: id.to_string(),
+            text: fill(text),
+            exceptions: Vec::new(),
+        });
+    }
+    for item in expression.licenses() {
+        let (LicenseId::Spdx(id), Some(exception_id)) = (&item.license, &item.exception) else {
+            continue;
+        };
+        let text = texts.iter_mut().find(|text| text.id == *id).unwrap();
+        if text.exceptions.iter().any(|(id, _)| id == exception_id) {
+            continue;
+        }
+        let exception = exceptions
+            .get_exception_case_insensitive(exception_id)
+            .unwrap();
+        if exception.is_deprecated_license_id {
+            eprintln!(
+                "{} The license exception id {} has been deprecated.",
+                badge_warning(),
+                exception_id.blue()
+            );
+        }
+        let details = exception.get_details(source).await?;
+        text.exceptions
+            .push((exception_id.clone(), fill(details.license_exception_text)));
+    }
+    Ok(texts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_read_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        fs::write(dir.join("Cargo.toml"), "[package]\nname = \"lic\"\n").unwrap();
+        fs::write(dir.join("package.json"), r#"{ "license": "MIT" }"#).unwrap();
+        // The manifest declaring a license is used.
+        let manifest = read_manifest(dir, None).unwrap();
+        assert_eq!(manifest.filename, "package.json");
+        assert_eq!(manifest.license.as_deref(), Some("MIT"));
+
+        fs::write(
+            dir.join("pyproject.toml"),
+            "[project]\nlicense = \"Apache-2.0\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            read_manifest(dir, None).err(),
+            Some(ManifestError::Conflict(vec![
+                ("pyproject.toml".into(), "Apache-2.0".into()),
+                ("package.json".into(), "MIT".into()),
+            ]))
+        );
+        let manifest = read_manifest(dir, Some(Ecosystem::Python)).unwrap();
+        assert_eq!(manifest.license.as_deref(), Some("Apache-2.0"));
+        assert_eq!(
+            read_manifest(dir, Some(Ecosystem::Rust)).err(),
+            Some(ManifestError::LicenseNotFound)
+        );
+    }
+
+    #[test]
+    fn test_same_license() {
+        assert!(same_license("MIT OR Apache-2.0", "MIT  OR Apache-2.0"));
+        assert!(same_license("MIT OR Apache-2.0", "Apache-2.0 OR mit"));
+        assert!(!same_license("MIT", "Apache-2.0"));
+    }
+}
@@ -0,0 +1,29 @@
+use super::files::{license_files, write_license_files};
+use super::{copyright, license_texts, load_licenses, parse_expression};
+use crate::cli::OptionsNew;
+use crate::config::Config;
+use crate::spdx::source::LicenseSource;
+use std::error::Error;
+use std::path::Path;
+
+pub async fn run(
+    options: OptionsNew,
+    config: &Config,
+    source: &dyn LicenseSource,
+) -> Result<(), Box<dyn Error>> {
+    let licenses = load_licenses(source).await?;
+    let Some(expression) = parse_expression(&options.id, &licenses, source).await? else {
+        return Err(format!("{} is not a valid license expression.", options.id).into());
+    };
+    let copyright = copyright(&options.copyright, &[], config);
+    let files = &options.files;
+    let texts = license_texts(&expression, &licenses, source, &copyright, files.width).await?;
+    if options.write {
+        let naming = files.naming.or(config.naming).unwrap_or_default();
+        write_license_files(Path::new(""), &license_files(&texts, naming), files.force)?;
+        return Ok(());
+    }
+    let texts: Vec<_> = texts.iter().map(|text| text.full_text()).collect();
+    print!("{}", texts.join("\n"));
+    Ok(())
+}
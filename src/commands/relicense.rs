@@ -0,0 +1,225 @@
+use super::files::{find_license_files, license_files};
+use super::{
+    badge_warning, cargo_license_dir, copyright, license_texts, load_licenses, manifest_dir,
+    parse_expression, read_manifest, same_license,
+};
+use crate::annotate::{identifier, replace_identifier};
+use crate::cli::OptionsRelicense;
+use crate::config::Config;
+use crate::manifest::{CargoToml, Manifest, PackageJson, PyprojectToml};
+use crate::spdx::expression::Expression;
+use crate::spdx::list::Licenses;
+use crate::spdx::matching::normalize;
+use crate::spdx::source::LicenseSource;
+use colored::*;
+use ignore::WalkBuilder;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A change of the plan, `text` being the new content of the file or `None`
+/// to remove it.
+struct Step {
+    /// Past and infinitive forms of what is done, e.g. ("Created", "create")
+    verb: (&'static str, &'static str),
+    path: PathBuf,
+    detail: String,
+    text: Option<String>,
+}
+
+/// The changes of a relicensing, and the files which need a look by hand
+/// with the reason.
+#[derive(Default)]
+struct Plan {
+    steps: Vec<Step>,
+    skipped: Vec<(PathBuf, String)>,
+}
+
+impl Plan {
+    fn skip(&mut self, path: &Path, reason: String) {
+        self.skipped.push((path.to_path_buf(), reason));
+    }
+}
+
+/// Plans the update of the license of the manifest `M` of `dir`.
+fn plan_manifest<M: Manifest>(dir: &Path, from: &str, to: &str, plan: &mut Plan) {
+    let Some(text) = M::read(dir) else {
+        return;
+    };
+    let path = dir.join(M::filename());
+    let license = match M::from_str(&text).and_then(M::license) {
+        Ok(license) => license,
+        Err(e) => return plan.skip(&path, e.to_string()),
+    };
+    if same_license(&license, to) {
+        return;
+    }
+    if !same_license(&license, from) {
+        return plan.skip(&path, format!("it declares {license}, not {from}"));
+    }
+    match M::set_license(&text, to) {
+        Ok(text) => plan.steps.push(Step {
+            verb: ("Set", "set"),
+            path,
+            detail: format!("{license} -> {to}"),
+            text: Some(text),
+        }),
+        Err(e) => plan.skip(&path, e.to_string()),
+    }
+}
+
+/// Plans the replacement of the license files of `from` in `dir` by `new`.
+async fn plan_license_files(
+    dir: &Path,
+    from: &Expression,
+    new: Vec<(PathBuf, String, String)>,
+    licenses: &Licenses,
+    source: &dyn LicenseSource,
+    plan: &mut Plan,
+) -> Result<(), Box<dyn Error>> {
+    let mut details = Vec::new();
+    for id in from.license_ids() {
+        let license = licenses.get_license(id).unwrap();
+        details.push(license.get_details(source).await?);
+    }
+    for path in find_license_files(&Path::new(".").join(dir))? {
+        if new.iter().any(|(new, _, _)| *new == path) {
+            continue;
+        }
+        let path = dir.join(path);
+        let text = fs::read_to_string(&path)
+            .map_err(|e| format!("Cannot read {}: {e}", path.display()))?;
+        let text = normalize(&text);
+        if details.iter().any(|details| details.is_contained_in(&text)) {
+            plan.steps.push(Step {
+                verb: ("Removed", "remove"),
+                path,
+                detail: from.to_string(),
+                text: None,
+            });
+        } else {
+            plan.skip(&path, format!("it has none of the licenses of {from}"));
+        }
+    }
+    for (path, ids, text) in new {
+        let path = dir.join(path);
+        let verb = match path.exists() {
+            true => ("Replaced", "replace"),
+            false => ("Created", "create"),
+        };
+        plan.steps.push(Step {
+            verb,
+            path,
+            detail: ids,
+            text: Some(text),
+        });
+    }
+    Ok(())
+}
+
+/// Plans the update of the `SPDX-License-Identifier` headers in `dir`.
+fn plan_sources(dir: &Path, from: &str, to: &str, plan: &mut Plan) {
+    let root = Path::new(".").join(dir);
+    for entry in WalkBuilder::new(&root).build().flatten() {
+        if !entry
+            .file_type()
+            .is_some_and(|file_type| file_type.is_file())
+        {
+            continue;
+        }
+        let path = dir.join(entry.path().strip_prefix(&root).unwrap_or(entry.path()));
+        // Binary files are not valid UTF-8 and have no header.
+        let Ok(text) = fs::read_to_string(entry.path()) else {
+            continue;
+        };
+        let Some(expression) = identifier(&text) else {
+            continue;
+        };
+        if same_license(&expression, to) {
+            continue;
+        }
+        if !same_license(&expression, from) {
+            plan.skip(&path, format!("its header is {expression}, not {from}"));
+            continue;
+        }
+        plan.steps.push(Step {
+            verb: ("Updated", "update"),
+            path,
+            detail: format!("{expression} -> {to}"),
+            text: replace_identifier(&text, to),
+        });
+    }
+}
+
+pub async fn run(
+    options: OptionsRelicense,
+    config: &Config,
+    source: &dyn LicenseSource,
+) -> Result<(), Box<dyn Error>> {
+    let dir = manifest_dir(None).unwrap_or_default();
+    let licenses = load_licenses(source).await?;
+    let Some(from) = parse_expression(&options.from, &licenses, source).await? else {
+        return Err(format!("{} is not a valid license expression.", options.from).into());
+    };
+    let Some(to) = parse_expression(&options.to, &licenses, source).await? else {
+        return Err(format!("{} is not a valid license expression.", options.to).into());
+    };
+    let (from_text, to_text) = (from.to_string(), to.to_string());
+
+    let mut plan = Plan::default();
+    plan_manifest::<CargoToml>(&cargo_license_dir(&dir)?, &from_text, &to_text, &mut plan);
+    plan_manifest::<PyprojectToml>(&dir, &from_text, &to_text, &mut plan);
+    plan_manifest::<PackageJson>(&dir, &from_text, &to_text, &mut plan);
+
+    let authors = read_manifest(&dir, None)
+        .map(|manifest| manifest.authors)
+        .unwrap_or_default();
+    let copyright = copyright(&options.copyright, &authors, config);
+    let texts = license_texts(&to, &licenses, source, &copyright, options.width).await?;
+    let naming = options.naming.or(config.naming).unwrap_or_default();
+    let new = license_files(&texts, naming)
+        .into_iter()
+        .map(|file| (file.path, file.ids.join(", "), file.text))
+        .collect();
+    plan_license_files(&dir, &from, new, &licenses, source, &mut plan).await?;
+    plan_sources(&dir, &from_text, &to_text, &mut plan);
+
+    for step in &plan.steps {
+        let verb = match options.dry_run {
+            true => format!("Would {}", step.verb.1),
+            false => step.verb.0.to_string(),
+        };
+        println!(
+            "{:>9} {} ({})",
+            verb.green().bold(),
+            step.path.display(),
+            step.detail
+        );
+        if options.dry_run {
+            continue;
+        }
+        match &step.text {
+            Some(text) => {
+                if let Some(parent) = step.path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(&step.path, text)?;
+            }
+            None => fs::remove_file(&step.path)?,
+        }
+    }
+    for (path, reason) in &plan.skipped {
+        eprintln!(
+            "{} {} is not handled, {reason}.",
+            badge_warning(),
+            path.display()
+        );
+    }
+    println!(
+        "{}{} changes, {} files not handled",
+        if options.dry_run { "(dry run) " } else { "" },
+        plan.steps.len(),
+        plan.skipped.len()
+    );
+    Ok(())
+}
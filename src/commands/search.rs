@@ -0,0 +1,89 @@
+use super::{load_exceptions, load_licenses};
+use crate::cli::OptionsSearch;
+use crate::spdx::source::LicenseSource;
+use colored::*;
+use std::error::Error;
+
+pub async fn run(options: OptionsSearch, source: &dyn LicenseSource) -> Result<(), Box<dyn Error>> {
+    if options.exceptions {
+        return search_exceptions(options, source).await;
+    }
+    let licenses = load_licenses(source).await?;
+    let similar_licenses = licenses.similar_licenses(&options.id, options.number);
+    let license_id_width = similar_licenses
+        .iter()
+        .map(|lic| lic.license_id.len())
+        .max()
+        .unwrap_or(0)
+        + 2;
+    let license_name_width = similar_licenses
+        .iter()
+        .map(|lic| lic.name.len())
+        .max()
+        .unwrap_or(0)
+        + 2;
+    println!(
+        "{:^license_id_width$} {:^license_name_width$} {:^12} {:^12}",
+        "License id".green(),
+        "License name".blue(),
+        "Deprecated".yellow(),
+        "OSI Approved".purple(),
+        license_id_width = license_id_width,
+        license_name_width = license_name_width,
+    );
+    for license in similar_licenses {
+        println!(
+            "{:license_id_width$} {:license_name_width$} {:^12} {:^12}",
+            license.license_id.green(),
+            license.name.blue(),
+            if license.is_deprecated_license_id {
+                "✔"
+            } else {
+                ""
+            },
+            if license.is_osi_approved { "✔" } else { "" },
+            license_id_width = license_id_width,
+            license_name_width = license_name_width,
+        );
+    }
+    Ok(())
+}
+
+async fn search_exceptions(
+    options: OptionsSearch,
+    source: &dyn LicenseSource,
+) -> Result<(), Box<dyn Error>> {
+    let exceptions = load_exceptions(source).await?;
+    let similar_exceptions = exceptions.similar_exceptions(&options.id, options.number);
+    let exception_id_width = similar_exceptions
+        .iter()
+        .map(|exception| exception.license_exception_id.len())
+        .max()
+        .unwrap_or(0)
+        + 2;
+    let exception_name_width = similar_exceptions
+        .iter()
+        .map(|exception| exception.name.len())
+        .max()
+        .unwrap_or(0)
+        + 2;
+    println!(
+        "{:^exception_id_width$} {:^exception_name_width$} {:^12}",
+        "Exception id".green(),
+        "Exception name".blue(),
+        "Deprecated".yellow(),
+    );
+    for exception in similar_exceptions {
+        println!(
+            "{:exception_id_width$} {:exception_name_width$} {:^12}",
+            exception.license_exception_id.green(),
+            exception.name.blue(),
+            if exception.is_deprecated_license_id {
+                "✔"
+            } else {
+                ""
+            },
+        );
+    }
+    Ok(())
+}
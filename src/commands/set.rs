@@ -0,0 +1,85 @@
+use super::auto::auto;
+use super::{
+    badge_warning, cargo_license_dir, load_licenses, manifest_dir, parse_expression, read_manifest,
+};
+use crate::cli::OptionsSet;
+use crate::config::Config;
+use crate::manifest::{CargoToml, Manifest, ManifestError, PackageJson, PyprojectToml};
+use crate::spdx::source::LicenseSource;
+use colored::*;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+/// Sets the license of the manifest `M` of `dir` to `license`, returning
+/// whether there is such a manifest.
+fn set_manifest<M: Manifest>(
+    dir: &Path,
+    license: &str,
+    dry_run: bool,
+) -> Result<bool, Box<dyn Error>> {
+    let Some(text) = M::read(dir) else {
+        return Ok(false);
+    };
+    let path = dir.join(M::filename());
+    let filename = path.display();
+    let old = M::from_str(&text).and_then(M::license).ok();
+    let new = match M::set_license(&text, license) {
+        Ok(new) => new,
+        Err(e) => {
+            eprintln!("{} {filename}: {e}", badge_warning());
+            return Ok(true);
+        }
+    };
+    if new == text {
+        println!("{:>9} {filename} ({license})", "Unchanged".bold());
+        return Ok(true);
+    }
+    let verb = if dry_run {
+        "Would set"
+    } else {
+        fs::write(&path, new)?;
+        "Set"
+    };
+    match old {
+        Some(old) => println!(
+            "{:>9} {filename} ({} -> {})",
+            verb.green().bold(),
+            old.yellow(),
+            license.green()
+        ),
+        None => println!(
+            "{:>9} {filename} ({})",
+            verb.green().bold(),
+            license.green()
+        ),
+    }
+    Ok(true)
+}
+
+pub async fn run(
+    options: OptionsSet,
+    config: &Config,
+    source: &dyn LicenseSource,
+) -> Result<(), Box<dyn Error>> {
+    let dir = manifest_dir(None)?;
+    let licenses = load_licenses(source).await?;
+    let Some(expression) = parse_expression(&options.id, &licenses, source).await? else {
+        return Err(format!("{} is not a valid license expression.", options.id).into());
+    };
+    let license = expression.to_string();
+    let found = [
+        set_manifest::<CargoToml>(&cargo_license_dir(&dir)?, &license, options.dry_run)?,
+        set_manifest::<PyprojectToml>(&dir, &license, options.dry_run)?,
+        set_manifest::<PackageJson>(&dir, &license, options.dry_run)?,
+    ];
+    if !found.contains(&true) {
+        return Err(ManifestError::FileNotFound.into());
+    }
+    if options.write {
+        let manifest = read_manifest(&dir, None)?;
+        let (files, copyright) = (&options.files, &options.copyright);
+        auto(&dir, manifest, files, copyright, config, &licenses, source).await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,279 @@
+//! Comment syntax of the languages license headers can be written in.
+
+use clap::ValueEnum;
+use std::path::Path;
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Lang {
+    #[value(alias = "rs")]
+    Rust,
+    #[value(alias = "h")]
+    C,
+    #[value(alias = "c++", alias = "cc", alias = "hpp")]
+    Cpp,
+    #[value(alias = "cs")]
+    Csharp,
+    Java,
+    #[value(alias = "kt")]
+    Kotlin,
+    Scala,
+    Swift,
+    Go,
+    Dart,
+    #[value(alias = "js")]
+    Javascript,
+    #[value(alias = "ts")]
+    Typescript,
+    Css,
+    Scss,
+    #[value(alias = "py")]
+    Python,
+    #[value(alias = "sh", alias = "bash", alias = "zsh")]
+    Shell,
+    #[value(alias = "rb")]
+    Ruby,
+    #[value(alias = "pl")]
+    Perl,
+    R,
+    Toml,
+    #[value(alias = "yml")]
+    Yaml,
+    #[value(alias = "make")]
+    Makefile,
+    Dockerfile,
+    Cmake,
+    Sql,
+    Lua,
+    #[value(alias = "hs")]
+    Haskell,
+    #[value(alias = "latex")]
+    Tex,
+    Erlang,
+    Html,
+    Xml,
+    Vue,
+    #[value(alias = "md")]
+    Markdown,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CommentStyle {
+    /// Every line starts with the prefix, e.g. `//`
+    Line(&'static str),
+    /// Wrapped in `start` and `end`, with every line starting with `prefix`
+    Block {
+        start: &'static str,
+        prefix: &'static str,
+        end: &'static str,
+    },
+}
+
+impl Lang {
+    /// Detects the language of a file from its name, or else from the
+    /// shebang on its `first_line`.
+    pub fn detect(path: &Path, first_line: &str) -> Option<Lang> {
+        Lang::from_path(path).or_else(|| Lang::from_shebang(first_line))
+    }
+
+    fn from_path(path: &Path) -> Option<Lang> {
+        let name = path.file_name()?.to_str()?;
+        let lang = match name {
+            "Makefile" | "GNUmakefile" | "makefile" => Lang::Makefile,
+            "Dockerfile" | "Containerfile" => Lang::Dockerfile,
+            "CMakeLists.txt" => Lang::Cmake,
+            "Rakefile" | "Gemfile" => Lang::Ruby,
+            _ => {
+                let extension = path.extension()?.to_str()?;
+                match extension.to_lowercase().as_str() {
+                    "rs" => Lang::Rust,
+                    "c" | "h" => Lang::C,
+                    "cpp" | "cc" | "cxx" | "hpp" | "hh" | "hxx" => Lang::Cpp,
+                    "cs" => Lang::Csharp,
+                    "java" => Lang::Java,
+                    "kt" | "kts" => Lang::Kotlin,
+                    "scala" => Lang::Scala,
+                    "swift" => Lang::Swift,
+                    "go" => Lang::Go,
+                    "dart" => Lang::Dart,
+                    "js" | "mjs" | "cjs" | "jsx" => Lang::Javascript,
+                    "ts" | "mts" | "cts" | "tsx" => Lang::Typescript,
+                    "css" => Lang::Css,
+                    "scss" => Lang::Scss,
+                    "py" | "pyi" => Lang::Python,
+                    "sh" | "bash" | "zsh" => Lang::Shell,
+                    "rb" => Lang::Ruby,
+                    "pl" | "pm" => Lang::Perl,
+                    "r" => Lang::R,
+                    "toml" => Lang::Toml,
+                    "yaml" | "yml" => Lang::Yaml,
+                    "mk" => Lang::Makefile,
+                    "cmake" => Lang::Cmake,
+                    "sql" => Lang::Sql,
+                    "lua" => Lang::Lua,
+                    "hs" => Lang::Haskell,
+                    "tex" | "sty" => Lang::Tex,
+                    "erl" | "hrl" => Lang::Erlang,
+                    "html" | "htm" => Lang::Html,
+                    "xml" => Lang::Xml,
+                    "vue" => Lang::Vue,
+                    "md" => Lang::Markdown,
+                    _ => return None,
+                }
+            }
+        };
+        Some(lang)
+    }
+
+    /// `#!/bin/sh`, `#!/usr/bin/env python3`, ...
+    fn from_shebang(line: &str) -> Option<Lang> {
+        let command = line.strip_prefix("#!")?;
+        let mut words = command.split_whitespace();
+        let mut program = words.next()?.rsplit('/').next()?;
+        if program == "env" {
+            program = words.find(|word| !word.starts_with('-'))?;
+        }
+        let program = program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+        let lang = match program {
+            "sh" | "bash" | "zsh" | "dash" | "ksh" => Lang::Shell,
+            "python" => Lang::Python,
+            "node" | "deno" => Lang::Javascript,
+            "ruby" => Lang::Ruby,
+            "perl" => Lang::Perl,
+            "lua" => Lang::Lua,
+            "Rscript" => Lang::R,
+            _ => return None,
+        };
+        Some(lang)
+    }
+
+    pub fn comment_style(self) -> CommentStyle {
+        match self {
+            Lang::Rust
+            | Lang::Cpp
+            | Lang::Csharp
+            | Lang::Java
+            | Lang::Kotlin
+            | Lang::Scala
+            | Lang::Swift
+            | Lang::Go
+            | Lang::Dart
+            | Lang::Javascript
+            | Lang::Typescript
+            | Lang::Scss => CommentStyle::Line("//"),
+            Lang::C | Lang::Css => CommentStyle::Block {
+                start: "/*",
+                prefix: " *",
+                end: " */",
+            },
+            Lang::Python
+            | Lang::Shell
+            | Lang::Ruby
+            | Lang::Perl
+            | Lang::R
+            | Lang::Toml
+            | Lang::Yaml
+            | Lang::Makefile
+            | Lang::Dockerfile
+            | Lang::Cmake => CommentStyle::Line("#"),
+            Lang::Sql | Lang::Lua | Lang::Haskell => CommentStyle::Line("--"),
+            Lang::Tex | Lang::Erlang => CommentStyle::Line("%"),
+            Lang::Html | Lang::Xml | Lang::Vue | Lang::Markdown => CommentStyle::Block {
+                start: "<!--",
+                prefix: "",
+                end: "-->",
+            },
+        }
+    }
+}
+
+impl CommentStyle {
+    /// Comments out every line of `text`.
+    pub fn comment(self, text: &str) -> String {
+        let prefixed = |prefix: &str, line: &str| {
+            if line.trim().is_empty() {
+                prefix.to_string()
+            } else if prefix.is_empty() {
+                line.to_string()
+            } else {
+                format!("{prefix} {line}")
+            }
+        };
+        let lines = text.trim_end().lines();
+        match self {
+            CommentStyle::Line(prefix) => lines.map(|line| prefixed(prefix, line) + "\n").collect(),
+            CommentStyle::Block { start, prefix, end } => {
+                let mut comment = format!("{start}\n");
+                for line in lines {
+                    comment += &prefixed(prefix, line);
+                    comment += "\n";
+                }
+                comment + end + "\n"
+            }
+        }
+    }
+
+    /// Width taken by the comment syntax at the start of every line.
+    pub fn indent(self) -> usize {
+        match self {
+            CommentStyle::Line(prefix) => prefix.len() + 1,
+            CommentStyle::Block { prefix: "", .. } => 0,
+            CommentStyle::Block { prefix, .. } => prefix.len() + 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_comment() {
+        let text = "SPDX-License-Identifier: MIT\n\nCopyright";
+        assert_eq!(
+            Lang::Rust.comment_style().comment(text),
+            "// SPDX-License-Identifier: MIT\n//\n// Copyright\n"
+        );
+        assert_eq!(
+            Lang::Python.comment_style().comment(text),
+            "# SPDX-License-Identifier: MIT\n#\n# Copyright\n"
+        );
+    }
+
+    #[test]
+    fn test_block_comment() {
+        let text = "SPDX-License-Identifier: MIT\n\nCopyright\n";
+        assert_eq!(
+            Lang::C.comment_style().comment(text),
+            "/*\n * SPDX-License-Identifier: MIT\n *\n * Copyright\n */\n"
+        );
+        assert_eq!(
+            Lang::Html.comment_style().comment(text),
+            "<!--\nSPDX-License-Identifier: MIT\n\nCopyright\n-->\n"
+        );
+    }
+
+    #[test]
+    fn test_detect() {
+        let detect = |path: &str, first_line| Lang::detect(Path::new(path), first_line);
+        assert_eq!(detect("src/main.rs", ""), Some(Lang::Rust));
+        assert_eq!(detect("include/lic.H", ""), Some(Lang::C));
+        assert_eq!(detect("docker/Dockerfile", ""), Some(Lang::Dockerfile));
+        assert_eq!(
+            detect("bin/lic", "#!/usr/bin/env python3"),
+            Some(Lang::Python)
+        );
+        assert_eq!(detect("bin/lic", "#!/bin/bash -e"), Some(Lang::Shell));
+        assert_eq!(
+            detect("bin/lic", "#!/usr/bin/env -S node --no-warnings"),
+            Some(Lang::Javascript)
+        );
+        assert_eq!(detect("package.json", ""), None);
+        assert_eq!(detect("LICENSE", "MIT License"), None);
+    }
+
+    #[test]
+    fn test_lang_alias() {
+        assert_eq!(Lang::from_str("rs", false), Ok(Lang::Rust));
+        assert_eq!(Lang::from_str("sh", false), Ok(Lang::Shell));
+    }
+}
@@ -0,0 +1,132 @@
+use crate::cli::Naming;
+use crate::spdx::template::Copyright;
+use serde::Deserialize;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Project level config, meant to be committed alongside the project.
+pub const PROJECT_CONFIG: &str = ".lic.toml";
+
+/// Defaults for the command line options, read from the user config
+/// (`$XDG_CONFIG_HOME/lic/config.toml`) and the project config (`.lic.toml`),
+/// the latter taking precedence.
+#[derive(Deserialize, PartialEq, Debug, Default)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Config {
+    pub list_version: Option<String>,
+    pub mirror: Option<String>,
+    pub data_dir: Option<PathBuf>,
+    pub cache_dir: Option<PathBuf>,
+    pub offline: Option<bool>,
+    pub naming: Option<Naming>,
+    pub holder: Option<String>,
+    pub year: Option<String>,
+    pub from_git: Option<bool>,
+}
+
+#[derive(Debug)]
+pub struct ConfigError {
+    path: PathBuf,
+    message: String,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Invalid config {}: {}",
+            self.path.display(),
+            self.message
+        )
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Config {
+    /// Default copyright holder and year.
+    pub fn copyright(&self) -> Copyright {
+        Copyright {
+            holder: self.holder.clone(),
+            year: self.year.clone(),
+        }
+    }
+
+    pub fn load() -> Result<Self, ConfigError> {
+        let user = match dirs::config_dir() {
+            Some(dir) => Config::from_file(&dir.join("lic").join("config.toml"))?,
+            None => Config::default(),
+        };
+        let project = Config::from_file(Path::new(PROJECT_CONFIG))?;
+        Ok(project.or(user))
+    }
+
+    /// A missing file is an empty config.
+    fn from_file(path: &Path) -> Result<Self, ConfigError> {
+        match fs::read_to_string(path) {
+            Ok(text) => Config::from_str(&text).map_err(|message| ConfigError {
+                path: path.into(),
+                message,
+            }),
+            Err(_) => Ok(Config::default()),
+        }
+    }
+
+    fn from_str(text: &str) -> Result<Self, String> {
+        toml::from_str(text).map_err(|e| e.message().to_string())
+    }
+
+    /// Fills the options missing in `self` from `other`.
+    fn or(self, other: Config) -> Config {
+        Config {
+            list_version: self.list_version.or(other.list_version),
+            mirror: self.mirror.or(other.mirror),
+            data_dir: self.data_dir.or(other.data_dir),
+            cache_dir: self.cache_dir.or(other.cache_dir),
+            offline: self.offline.or(other.offline),
+            naming: self.naming.or(other.naming),
+            holder: self.holder.or(other.holder),
+            year: self.year.or(other.year),
+            from_git: self.from_git.or(other.from_git),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config() {
+        let data = r#"
+        list-version = "3.21"
+        offline = true
+        naming = "suffix"
+        "#;
+        let config = Config::from_str(data).unwrap();
+        assert_eq!(
+            config,
+            Config {
+                list_version: Some(String::from("3.21")),
+                offline: Some(true),
+                naming: Some(Naming::Suffix),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_config_unknown_key() {
+        assert!(Config::from_str("list_version = \"3.21\"").is_err());
+    }
+
+    #[test]
+    fn test_config_precedence() {
+        let project = Config::from_str("list-version = \"3.21\"").unwrap();
+        let user = Config::from_str("list-version = \"3.16\"\noffline = true").unwrap();
+        let config = project.or(user);
+        assert_eq!(config.list_version, Some(String::from("3.21")));
+        assert_eq!(config.offline, Some(true));
+    }
+}
@@ -0,0 +1,125 @@
+//! Copyright holder and years from the history of the local git repository.
+
+use crate::spdx::template::Copyright;
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Runs git in `dir`, returning its trimmed output if it succeeded with any.
+fn git(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = String::from_utf8(output.stdout).ok()?;
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// The copyright of the repository containing `dir`: the configured
+/// `user.name` (or else the most frequent author) and the years from the
+/// first commit to now. `None` outside a git repository.
+pub fn copyright(dir: &Path) -> Option<Copyright> {
+    git(dir, &["rev-parse", "--git-dir"])?;
+    let holder = git(dir, &["config", "user.name"]).or_else(|| {
+        let log = git(dir, &["log", "--no-merges", "--format=%an"])?;
+        most_frequent_author(&log)
+    });
+    let first_year = git(dir, &["log", "--max-parents=0", "--format=%at"])
+        .and_then(|log| log.lines().filter_map(|line| line.parse().ok()).min())
+        .map(year_of);
+    let current_year = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|now| year_of(now.as_secs() as i64))
+        .ok();
+    let year = match (first_year, current_year) {
+        (Some(first), Some(current)) => Some(year_range(first, current)),
+        (first, current) => first.or(current).map(|year| year.to_string()),
+    };
+    Some(Copyright { holder, year })
+}
+
+/// The author with the most commits in `git log --format=%an` output, the
+/// most recent one on ties.
+fn most_frequent_author(log: &str) -> Option<String> {
+    let mut commits: HashMap<&str, usize> = HashMap::new();
+    let mut authors = Vec::new();
+    for author in log.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        let count = commits.entry(author).or_insert(0);
+        if *count == 0 {
+            authors.push(author);
+        }
+        *count += 1;
+    }
+    // `max_by_key` keeps the last maximum, so iterate from the oldest author.
+    authors
+        .into_iter()
+        .rev()
+        .max_by_key(|author| commits[author])
+        .map(str::to_string)
+}
+
+fn year_range(first: i64, current: i64) -> String {
+    if first < current {
+        format!("{first}-{current}")
+    } else {
+        current.to_string()
+    }
+}
+
+/// UTC year of a unix timestamp.
+fn year_of(timestamp: i64) -> i64 {
+    // https://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let days = timestamp.div_euclid(86400) + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let year = year_of_era + era * 400;
+    // The computed year starts in March.
+    if month >= 10 {
+        year + 1
+    } else {
+        year
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_year_of() {
+        assert_eq!(year_of(0), 1970);
+        // 2023-01-01T00:00:00Z and one second before
+        assert_eq!(year_of(1672531200), 2023);
+        assert_eq!(year_of(1672531199), 2022);
+        // 2024-02-29T12:00:00Z
+        assert_eq!(year_of(1709208000), 2024);
+    }
+
+    #[test]
+    fn test_year_range() {
+        assert_eq!(year_range(2021, 2023), "2021-2023");
+        assert_eq!(year_range(2023, 2023), "2023");
+    }
+
+    #[test]
+    fn test_most_frequent_author() {
+        let log = "Nyakku Shigure\nSigureMo\nNyakku Shigure\n";
+        assert_eq!(most_frequent_author(log), Some("Nyakku Shigure".into()));
+        assert_eq!(
+            most_frequent_author("SigureMo\nNyakku Shigure"),
+            Some("SigureMo".into())
+        );
+        assert_eq!(most_frequent_author(""), None);
+    }
+}
@@ -1,23 +1,325 @@
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use ignore::WalkBuilder;
+use std::fmt;
 use std::fs;
 use std::marker::Sized;
+use std::path::{Path, PathBuf};
+use toml_edit::{DocumentMut, Item};
+
 #[derive(Debug, PartialEq)]
 pub enum ManifestError {
     LicenseNotFound,
     FileNotFound,
     ParseError,
+    WorkspaceNotFound,
+    /// The package is `UNLICENSED`, it grants no license at all
+    Unlicensed,
+    /// Several manifests declare different licenses, as (filename, license)
+    Conflict(Vec<(String, String)>),
 }
 
+impl fmt::Display for ManifestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ManifestError::LicenseNotFound => {
+                write!(f, "Cannot find the license id in the manifest file.")
+            }
+            ManifestError::FileNotFound => write!(f, "Cannot find the manifest file."),
+            ManifestError::ParseError => write!(f, "Cannot parse the manifest file."),
+            ManifestError::Unlicensed => {
+                write!(f, "The package is UNLICENSED, it grants no license.")
+            }
+            ManifestError::Conflict(licenses) => {
+                let licenses: Vec<_> = licenses
+                    .iter()
+                    .map(|(filename, license)| format!("{filename} ({license})"))
+                    .collect();
+                write!(
+                    f,
+                    "The manifest files declare different licenses: {}.",
+                    licenses.join(", ")
+                )
+            }
+            ManifestError::WorkspaceNotFound => {
+                write!(
+                    f,
+                    "Cannot find the workspace the manifest file inherits from."
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ManifestError {}
+
 pub trait Manifest {
     fn license(self) -> Result<String, ManifestError>;
+    /// Author names, without their email or url
+    fn authors(&self) -> Vec<String>;
+    /// License file the manifest points to instead of declaring a license id,
+    /// relative to the manifest
+    fn license_file(&self) -> Option<String> {
+        None
+    }
+    /// Glob patterns of the license files, relative to the manifest
+    fn license_files(&self) -> Option<Vec<String>> {
+        None
+    }
+    /// Inconsistencies of the manifest worth a warning
+    fn warnings(&self) -> Vec<String> {
+        Vec::new()
+    }
+    /// Directories of the workspace members, relative to the workspace
+    /// `root` of the manifest
+    fn members(&self, _root: &Path) -> Result<Vec<PathBuf>, ManifestError> {
+        Ok(Vec::new())
+    }
     fn filename() -> String;
     fn from_str(text: &str) -> Result<Self, ManifestError>
     where
         Self: Sized;
-    fn read() -> Option<String> {
-        // TODO: 自动向上层寻找
-        match fs::read_to_string(Self::filename()) {
-            Ok(text) => Some(text),
-            Err(_) => None,
+    /// Fills in what the manifest in `dir` inherits from elsewhere, e.g. the
+    /// `workspace = true` fields of a Cargo workspace member
+    fn resolve(self, _dir: &Path) -> Result<Self, ManifestError>
+    where
+        Self: Sized,
+    {
+        Ok(self)
+    }
+    /// Sets the license in the manifest `text`, replacing the declared one or
+    /// adding it, and keeping the formatting of the rest of the manifest
+    fn set_license(text: &str, license: &str) -> Result<String, ManifestError>
+    where
+        Self: Sized;
+    fn read(dir: &Path) -> Option<String> {
+        fs::read_to_string(dir.join(Self::filename())).ok()
+    }
+}
+
+/// Directories marking the root of a repository.
+const VCS_DIRS: &[&str] = &[".git", ".hg", ".svn", ".jj"];
+
+/// Device of a path, to stop at file system boundaries.
+#[cfg(unix)]
+fn device(path: &Path) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    fs::metadata(path).ok().map(|metadata| metadata.dev())
+}
+
+#[cfg(not(unix))]
+fn device(_path: &Path) -> Option<u64> {
+    None
+}
+
+/// Finds the nearest directory from `dir` up which has one of the manifests
+/// `filenames`, as a path relative to `dir`. The search stops at the root of
+/// the repository and at file system boundaries.
+pub fn find_manifest_dir(dir: &Path, filenames: &[String]) -> Option<PathBuf> {
+    let start = Path::new(".").join(dir).canonicalize().ok()?;
+    let start_device = device(&start);
+    let mut relative = PathBuf::new();
+    for ancestor in start.ancestors() {
+        if device(ancestor) != start_device {
+            return None;
+        }
+        if filenames
+            .iter()
+            .any(|filename| ancestor.join(filename).is_file())
+        {
+            return Some(relative);
+        }
+        if VCS_DIRS.iter().any(|vcs| ancestor.join(vcs).exists()) {
+            return None;
+        }
+        relative.push("..");
+    }
+    None
+}
+
+/// Strips the email and url from a `Name <email> (url)` author.
+pub fn author_name(author: &str) -> String {
+    let end = author.find(['<', '(']).unwrap_or(author.len());
+    author[..end].trim().to_string()
+}
+
+fn member_globs(patterns: &[String]) -> Result<GlobSet, ManifestError> {
+    let mut globs = GlobSetBuilder::new();
+    for pattern in patterns {
+        let pattern = pattern.trim_start_matches("./").trim_end_matches('/');
+        let glob = GlobBuilder::new(pattern)
+            .literal_separator(true)
+            .build()
+            .map_err(|_| ManifestError::ParseError)?;
+        globs.add(glob);
+    }
+    globs.build().map_err(|_| ManifestError::ParseError)
+}
+
+/// Finds the directories under `root` matched by the `members` patterns of a
+/// workspace and not by the `exclude` ones, which have a manifest named
+/// `filename`, as paths relative to `root`.
+pub fn find_members(
+    root: &Path,
+    members: &[String],
+    exclude: &[String],
+    filename: &str,
+) -> Result<Vec<PathBuf>, ManifestError> {
+    let members = member_globs(members)?;
+    let exclude = member_globs(exclude)?;
+    let root = Path::new(".").join(root);
+    let walker = WalkBuilder::new(&root)
+        .filter_entry(|entry| {
+            !matches!(entry.file_name().to_str(), Some("target" | "node_modules"))
+        })
+        .build();
+    let mut dirs = Vec::new();
+    for entry in walker.flatten() {
+        if !entry
+            .file_type()
+            .is_some_and(|file_type| file_type.is_dir())
+        {
+            continue;
         }
+        let path = entry.path().strip_prefix(&root).unwrap_or(entry.path());
+        if path.as_os_str().is_empty() || !members.is_match(path) || exclude.is_match(path) {
+            continue;
+        }
+        if entry.path().join(filename).is_file() {
+            dirs.push(path.to_path_buf());
+        }
+    }
+    dirs.sort();
+    Ok(dirs)
+}
+
+/// Replaces the string at `path` in a TOML document, keeping its formatting
+/// and comments.
+pub fn set_toml_string(text: &str, path: &[&str], value: &str) -> Result<String, ManifestError> {
+    let mut document: DocumentMut = text.parse().map_err(|_| ManifestError::ParseError)?;
+    let mut item: &mut Item = document.as_item_mut();
+    for key in path {
+        item = item.get_mut(key).ok_or(ManifestError::LicenseNotFound)?;
+    }
+    let old = item
+        .as_value_mut()
+        .filter(|old| old.is_str())
+        .ok_or(ManifestError::LicenseNotFound)?;
+    let decor = old.decor().clone();
+    *old = value.into();
+    *old.decor_mut() = decor;
+    Ok(document.to_string())
+}
+
+/// Sets `key` to the string `value` in the first of the `tables` present in
+/// a TOML document, replacing its value if any as well as the `replaced` keys
+/// of that table, and keeping the formatting and comments of the rest of the
+/// document.
+pub fn insert_toml_string(
+    text: &str,
+    tables: &[&[&str]],
+    key: &str,
+    replaced: &[&str],
+    value: &str,
+) -> Result<String, ManifestError> {
+    let mut document: DocumentMut = text.parse().map_err(|_| ManifestError::ParseError)?;
+    let get = |path: &[&str]| {
+        path.iter()
+            .try_fold(document.as_item(), |item, key| item.get(key))
+            .filter(|item| item.is_table_like())
+            .is_some()
+    };
+    let table = tables
+        .iter()
+        .find(|path| get(path))
+        .ok_or(ManifestError::LicenseNotFound)?;
+    let mut item = document.as_item_mut();
+    for key in table.iter() {
+        item = item.get_mut(key).unwrap();
+    }
+    let table = item.as_table_like_mut().unwrap();
+    for key in replaced {
+        table.remove(key);
+    }
+    table.insert(key, toml_edit::value(value));
+    Ok(document.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_author_name() {
+        assert_eq!(author_name("Nyakku Shigure"), "Nyakku Shigure");
+        assert_eq!(
+            author_name("SigureMo <sigure.qaq@gmail.com> (https://github.com/SigureMo)"),
+            "SigureMo"
+        );
+    }
+
+    #[test]
+    fn test_insert_toml_string() {
+        let text = "[project]\nname = \"lic\" # the name\n\n[tool.poetry]\n";
+        assert_eq!(
+            insert_toml_string(
+                text,
+                &[&["project"], &["tool", "poetry"]],
+                "license",
+                &[],
+                "MIT"
+            ),
+            Ok("[project]\nname = \"lic\" # the name\nlicense = \"MIT\"\n\n[tool.poetry]\n".into())
+        );
+        let text = "[tool.poetry]\nlicense = { file = \"LICENSE\" }\n";
+        assert_eq!(
+            insert_toml_string(
+                text,
+                &[&["project"], &["tool", "poetry"]],
+                "license",
+                &[],
+                "MIT"
+            ),
+            Ok("[tool.poetry]\nlicense = \"MIT\"\n".into())
+        );
+        assert_eq!(
+            insert_toml_string("[tool.black]\n", &[&["project"]], "license", &[], "MIT"),
+            Err(ManifestError::LicenseNotFound)
+        );
+    }
+
+    #[test]
+    fn test_find_manifest_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let filenames = [String::from("Cargo.toml"), String::from("package.json")];
+        fs::create_dir_all(dir.path().join("repo/crate/src/bin")).unwrap();
+        fs::create_dir_all(dir.path().join("repo/.git")).unwrap();
+        fs::write(dir.path().join("package.json"), "{}").unwrap();
+        fs::write(dir.path().join("repo/crate/Cargo.toml"), "").unwrap();
+        assert_eq!(
+            find_manifest_dir(&dir.path().join("repo/crate/src/bin"), &filenames),
+            Some(PathBuf::from("../.."))
+        );
+        assert_eq!(
+            find_manifest_dir(&dir.path().join("repo/crate"), &filenames),
+            Some(PathBuf::new())
+        );
+        // The `package.json` out of the repository is not its manifest.
+        assert_eq!(
+            find_manifest_dir(&dir.path().join("repo"), &filenames),
+            None
+        );
+    }
+
+    #[test]
+    fn test_set_toml_string() {
+        let text = "[package]\nname = \"lic\"\nlicense = \"GPL-3.0\" # deprecated\n";
+        assert_eq!(
+            set_toml_string(text, &["package", "license"], "GPL-3.0-only"),
+            Ok("[package]\nname = \"lic\"\nlicense = \"GPL-3.0-only\" # deprecated\n".into())
+        );
+        assert_eq!(
+            set_toml_string(text, &["package", "authors"], "Nyakku Shigure"),
+            Err(ManifestError::LicenseNotFound)
+        );
     }
 }
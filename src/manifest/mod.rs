@@ -3,7 +3,7 @@ mod nodejs;
 mod python;
 mod rust;
 
-pub use base::{Manifest, ManifestError};
-pub use nodejs::PackageJson;
+pub use base::{find_manifest_dir, Manifest, ManifestError};
+pub use nodejs::{PackageJson, UNLICENSED};
 pub use python::PyprojectToml;
 pub use rust::CargoToml;
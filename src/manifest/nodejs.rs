@@ -1,15 +1,151 @@
-use super::base::{Manifest, ManifestError};
+use super::base::{author_name, find_members, Manifest, ManifestError};
 use serde::Deserialize;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
 #[derive(Deserialize, PartialEq, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct PackageJson {
     // https://docs.npmjs.com/cli/v7/configuring-npm/package-json
-    license: Option<String>,
+    license: Option<License>,
+    /// Deprecated list of licenses, any of which applies
+    licenses: Option<Vec<License>>,
+    author: Option<Person>,
+    workspaces: Option<Workspaces>,
+}
+
+/// A SPDX expression, or the deprecated `{ "type": ..., "url": ... }` form.
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(untagged)]
+pub enum License {
+    Expression(String),
+    Object {
+        #[serde(rename = "type")]
+        kind: String,
+    },
+}
+
+/// `"Name <email> (url)"` or `{ "name": ..., "email": ..., "url": ... }`
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(untagged)]
+pub enum Person {
+    Text(String),
+    Object { name: Option<String> },
+}
+
+/// Glob patterns of the workspace packages, as npm, or as yarn with
+/// `{ "packages": [...] }`.
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(untagged)]
+pub enum Workspaces {
+    Patterns(Vec<String>),
+    Object { packages: Vec<String> },
+}
+
+/// The license of a package which grants no license.
+pub const UNLICENSED: &str = "UNLICENSED";
+const SEE_LICENSE_IN: &str = "SEE LICENSE IN ";
+
+impl License {
+    fn as_str(&self) -> &str {
+        match self {
+            License::Expression(license) => license,
+            License::Object { kind } => kind,
+        }
+    }
+}
+
+impl PackageJson {
+    /// The declared license, the ids of the deprecated list being joined
+    /// into an `OR` expression.
+    fn declared_license(&self) -> Option<String> {
+        if let Some(license) = &self.license {
+            return Some(license.as_str().trim().to_string());
+        }
+        let licenses: Vec<_> = self
+            .licenses
+            .as_ref()?
+            .iter()
+            .map(License::as_str)
+            .collect();
+        match licenses.as_slice() {
+            [] => None,
+            [license] => Some(license.trim().to_string()),
+            licenses => Some(
+                licenses
+                    .iter()
+                    .map(|license| match license.contains(' ') {
+                        true => format!("({})", license.trim()),
+                        false => license.to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" OR "),
+            ),
+        }
+    }
 }
 
 impl Manifest for PackageJson {
     fn license(self) -> Result<String, ManifestError> {
-        self.license.ok_or(ManifestError::LicenseNotFound)
+        match self.declared_license() {
+            Some(license) if license == UNLICENSED => Err(ManifestError::Unlicensed),
+            Some(license) if license.starts_with(SEE_LICENSE_IN) => {
+                Err(ManifestError::LicenseNotFound)
+            }
+            license => license.ok_or(ManifestError::LicenseNotFound),
+        }
+    }
+
+    fn license_file(&self) -> Option<String> {
+        let license = self.declared_license()?;
+        let file = license.strip_prefix(SEE_LICENSE_IN)?.trim();
+        Some(file.to_string())
+    }
+
+    fn authors(&self) -> Vec<String> {
+        match &self.author {
+            Some(Person::Text(author)) => vec![author_name(author)],
+            Some(Person::Object { name: Some(name) }) => vec![name.clone()],
+            _ => Vec::new(),
+        }
+    }
+
+    fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if matches!(self.license, Some(License::Object { .. })) {
+            warnings.push(
+                "The license object of package.json is deprecated, use a SPDX expression string instead."
+                    .into(),
+            );
+        }
+        if self.licenses.is_some() {
+            warnings.push(
+                "The licenses list of package.json is deprecated, use a SPDX expression string instead."
+                    .into(),
+            );
+        }
+        warnings
+    }
+
+    /// The directories of the workspace packages, the root itself being
+    /// included (as an empty path) if it declares a license.
+    fn members(&self, root: &Path) -> Result<Vec<PathBuf>, ManifestError> {
+        let patterns = match &self.workspaces {
+            Some(Workspaces::Patterns(patterns)) => patterns,
+            Some(Workspaces::Object { packages }) => packages,
+            None => return Ok(Vec::new()),
+        };
+        let (exclude, members): (Vec<String>, Vec<String>) = patterns
+            .iter()
+            .cloned()
+            .partition(|pattern| pattern.starts_with('!'));
+        let exclude: Vec<String> = exclude.iter().map(|pattern| pattern[1..].into()).collect();
+        let mut dirs = Vec::new();
+        if self.declared_license().is_some() {
+            dirs.push(PathBuf::new());
+        }
+        dirs.extend(find_members(root, &members, &exclude, &Self::filename())?);
+        Ok(dirs)
     }
 
     fn filename() -> String {
@@ -19,6 +155,150 @@ impl Manifest for PackageJson {
     fn from_str(text: &str) -> Result<Self, ManifestError> {
         serde_json::from_str(text).map_err(|_| ManifestError::ParseError)
     }
+
+    fn set_license(text: &str, license: &str) -> Result<String, ManifestError> {
+        serde_json::from_str::<serde_json::Value>(text).map_err(|_| ManifestError::ParseError)?;
+        let value = serde_json::to_string(license).unwrap();
+        // The deprecated `licenses` list is replaced by the expression.
+        let Some((_, range)) = top_level_entry(text, "license") else {
+            return match top_level_entry(text, "licenses") {
+                Some((start, range)) => Ok(format!(
+                    "{}\"license\": {value}{}",
+                    &text[..start],
+                    &text[range.end..]
+                )),
+                None => push_top_level(text, "license", &value).ok_or(ManifestError::ParseError),
+            };
+        };
+        let text = format!("{}{value}{}", &text[..range.start], &text[range.end..]);
+        Ok(remove_top_level(&text, "licenses").unwrap_or(text))
+    }
+}
+
+/// End of the JSON string starting at `start`, after its closing quote.
+fn string_end(bytes: &[u8], start: usize) -> Option<usize> {
+    let mut i = start + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'"' => return Some(i + 1),
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+/// End of the JSON value starting at `start` in a valid JSON text.
+fn value_end(bytes: &[u8], start: usize) -> Option<usize> {
+    let mut depth = 0;
+    let mut i = start;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' => {
+                i = string_end(bytes, i)?;
+                if depth == 0 {
+                    return Some(i);
+                }
+                continue;
+            }
+            b'{' | b'[' => depth += 1,
+            b'}' | b']' if depth == 0 => return Some(i),
+            b'}' | b']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i + 1);
+                }
+            }
+            b',' if depth == 0 => return Some(i),
+            c if c.is_ascii_whitespace() && depth == 0 => return Some(i),
+            _ => {}
+        }
+        i += 1;
+    }
+    Some(i)
+}
+
+/// Start of `key` in the top-level object of a valid JSON `text`, and byte
+/// range of its value, e.g. a string with its quotes or a whole object.
+fn top_level_entry(text: &str, key: &str) -> Option<(usize, Range<usize>)> {
+    let bytes = text.as_bytes();
+    let skip_whitespace = |mut i: usize| {
+        while bytes.get(i).is_some_and(u8::is_ascii_whitespace) {
+            i += 1;
+        }
+        i
+    };
+    let (mut depth, mut previous, mut i) = (0, b' ', 0);
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' => {
+                let end = string_end(bytes, i)?;
+                let is_key = depth == 1 && matches!(previous, b'{' | b',');
+                if is_key && serde_json::from_str::<String>(&text[i..end]).ok()? == key {
+                    let colon = skip_whitespace(end);
+                    let start = skip_whitespace(colon + 1);
+                    return Some((i, start..value_end(bytes, start)?));
+                }
+                previous = b'"';
+                i = end;
+                continue;
+            }
+            b'{' | b'[' => depth += 1,
+            b'}' | b']' => depth -= 1,
+            c if c.is_ascii_whitespace() => {
+                i += 1;
+                continue;
+            }
+            _ => {}
+        }
+        previous = bytes[i];
+        i += 1;
+    }
+    None
+}
+
+/// Removes `key` and its value from the top-level object of a valid JSON
+/// `text`, with the comma separating it from the other keys.
+fn remove_top_level(text: &str, key: &str) -> Option<String> {
+    let (start, range) = top_level_entry(text, key)?;
+    let after = text[range.end..].trim_start();
+    if let Some(next) = after.strip_prefix(',') {
+        let next = text.len() - next.trim_start().len();
+        return Some(format!("{}{}", &text[..start], &text[next..]));
+    }
+    let before = text[..start].trim_end();
+    let start = before.strip_suffix(',').map_or(start, str::len);
+    Some(format!("{}{}", &text[..start], &text[range.end..]))
+}
+
+/// Adds `key` with the JSON `value` at the end of the top-level object of a
+/// valid JSON `text`, indented like the other keys, or as npm does in an
+/// empty object.
+fn push_top_level(text: &str, key: &str, value: &str) -> Option<String> {
+    let open = text.find('{')?;
+    let close = text.rfind('}')?;
+    let end = text[..close].trim_end().len();
+    let key = serde_json::to_string(key).unwrap();
+    if end == open + 1 {
+        return Some(format!(
+            "{}\n  {key}: {value}\n{}",
+            &text[..end],
+            &text[close..]
+        ));
+    }
+    let first = &text[open + 1..];
+    let whitespace = &first[..first.len() - first.trim_start().len()];
+    let indent = match whitespace.rfind('\n') {
+        Some(newline) if first.trim_start().starts_with('"') => {
+            format!("\n{}", &whitespace[newline + 1..])
+        }
+        _ => String::from(" "),
+    };
+    Some(format!(
+        "{},{indent}{key}: {value}{}",
+        &text[..end],
+        &text[end..]
+    ))
 }
 
 #[cfg(test)]
@@ -44,9 +324,15 @@ mod tests {
         assert_eq!(
             lic,
             PackageJson {
-                license: Some(String::from("MIT"))
+                license: Some(License::Expression(String::from("MIT"))),
+                licenses: None,
+                author: Some(Person::Text(String::from(
+                    "SigureMo <sigure.qaq@gmail.com>"
+                ))),
+                workspaces: None,
             }
         );
+        assert_eq!(lic.authors(), vec![String::from("SigureMo")]);
         assert_eq!(lic.license(), Ok(String::from("MIT")));
     }
 
@@ -57,8 +343,76 @@ mod tests {
             "name": "moefy-canvas-monorepo"
         }"#;
         let lic: PackageJson = serde_json::from_str(data).unwrap();
-        assert_eq!(lic, PackageJson { license: None });
+        assert_eq!(
+            lic,
+            PackageJson {
+                license: None,
+                licenses: None,
+                author: None,
+                workspaces: None,
+            }
+        );
+        assert_eq!(lic.license(), Err(ManifestError::LicenseNotFound));
+    }
+
+    #[test]
+    fn test_package_json_legacy_license() {
+        let lic = PackageJson::from_str(
+            r#"{ "license": { "type": "MIT", "url": "https://opensource.org/licenses/MIT" } }"#,
+        )
+        .unwrap();
+        assert_eq!(lic.warnings().len(), 1);
+        assert_eq!(lic.license(), Ok(String::from("MIT")));
+
+        let lic = PackageJson::from_str(
+            r#"{
+                "licenses": [
+                    { "type": "MIT", "url": "https://opensource.org/licenses/MIT" },
+                    { "type": "Apache-2.0", "url": "https://opensource.org/licenses/Apache-2.0" }
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(lic.warnings().len(), 1);
+        assert_eq!(lic.license(), Ok(String::from("MIT OR Apache-2.0")));
+
+        let lic = PackageJson::from_str(r#"{ "license": "SEE LICENSE IN LICENSE.txt" }"#).unwrap();
+        assert_eq!(lic.license_file(), Some(String::from("LICENSE.txt")));
         assert_eq!(lic.license(), Err(ManifestError::LicenseNotFound));
+
+        let lic = PackageJson::from_str(r#"{ "license": "UNLICENSED" }"#).unwrap();
+        assert_eq!(lic.license_file(), None);
+        assert_eq!(lic.license(), Err(ManifestError::Unlicensed));
+    }
+
+    #[test]
+    fn test_package_json_workspaces() {
+        let dir = tempfile::tempdir().unwrap();
+        for package in ["packages/a", "packages/b", "packages/private", "docs"] {
+            let path = dir.path().join(package);
+            std::fs::create_dir_all(&path).unwrap();
+            std::fs::write(path.join("package.json"), "{}").unwrap();
+        }
+        std::fs::create_dir_all(dir.path().join("packages/assets")).unwrap();
+        let lic = PackageJson::from_str(
+            r#"{ "private": true, "workspaces": ["packages/*", "!packages/private"] }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            lic.members(dir.path()),
+            Ok(vec![
+                PathBuf::from("packages/a"),
+                PathBuf::from("packages/b")
+            ])
+        );
+        let lic = PackageJson::from_str(
+            r#"{ "license": "MIT", "workspaces": { "packages": ["docs"] } }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            lic.members(dir.path()),
+            Ok(vec![PathBuf::new(), PathBuf::from("docs")])
+        );
     }
 
     #[test]
@@ -71,4 +425,58 @@ mod tests {
         let lic = PackageJson::from_str(data);
         assert_eq!(lic, Err(ManifestError::ParseError));
     }
+
+    #[test]
+    fn test_package_json_set_license() {
+        let data = r#"{
+  "name": "lic",
+  "scripts": { "license": "lic check" },
+  "license": "GPL-3.0"
+}
+"#;
+        assert_eq!(
+            PackageJson::set_license(data, "GPL-3.0-only"),
+            Ok(data.replace(r#""GPL-3.0""#, r#""GPL-3.0-only""#))
+        );
+        assert_eq!(
+            PackageJson::set_license(
+                r#"{ "license": { "type": "MIT" }, "private": true }"#,
+                "MIT"
+            ),
+            Ok(r#"{ "license": "MIT", "private": true }"#.into())
+        );
+        assert_eq!(
+            PackageJson::set_license(r#"{ "name": "lic" }"#, "MIT"),
+            Ok(r#"{ "name": "lic", "license": "MIT" }"#.into())
+        );
+        assert_eq!(
+            PackageJson::set_license("{\n    \"name\": \"lic\"\n}\n", "MIT"),
+            Ok("{\n    \"name\": \"lic\",\n    \"license\": \"MIT\"\n}\n".into())
+        );
+        assert_eq!(
+            PackageJson::set_license("{}\n", "MIT"),
+            Ok("{\n  \"license\": \"MIT\"\n}\n".into())
+        );
+        assert_eq!(
+            PackageJson::set_license(
+                r#"{ "licenses": [{ "type": "MIT" }], "private": true }"#,
+                "MIT"
+            ),
+            Ok(r#"{ "license": "MIT", "private": true }"#.into())
+        );
+        assert_eq!(
+            PackageJson::set_license(
+                "{\n  \"license\": \"MIT\",\n  \"licenses\": [{ \"type\": \"MIT\" }],\n  \"private\": true\n}\n",
+                "ISC"
+            ),
+            Ok("{\n  \"license\": \"ISC\",\n  \"private\": true\n}\n".into())
+        );
+        assert_eq!(
+            PackageJson::set_license(
+                r#"{ "license": "MIT", "licenses": [{ "type": "MIT" }] }"#,
+                "ISC"
+            ),
+            Ok(r#"{ "license": "ISC" }"#.into())
+        );
+    }
 }
@@ -1,6 +1,97 @@
-use super::base::{Manifest, ManifestError};
+use super::base::{author_name, insert_toml_string, set_toml_string, Manifest, ManifestError};
+use crate::spdx::aliases::may_be_same;
+use crate::spdx::expression::Expression;
 use serde::Deserialize;
 
+/// License classifiers of https://pypi.org/classifiers/ and the SPDX ids
+/// they mean. The unversioned ones are mapped to one of the [`FAMILIES`].
+const CLASSIFIERS: &[(&str, &str)] = &[
+    ("License :: CC0 1.0 Universal (CC0 1.0) Public Domain Dedication", "CC0-1.0"),
+    ("License :: OSI Approved :: Academic Free License (AFL)", "AFL-3.0"),
+    ("License :: OSI Approved :: Apache Software License", "Apache"),
+    ("License :: OSI Approved :: Apple Public Source License", "APSL-2.0"),
+    ("License :: OSI Approved :: Artistic License", "Artistic"),
+    ("License :: OSI Approved :: Attribution Assurance License", "AAL"),
+    ("License :: OSI Approved :: Blue Oak Model License (BlueOak-1.0.0)", "BlueOak-1.0.0"),
+    ("License :: OSI Approved :: Boost Software License 1.0 (BSL-1.0)", "BSL-1.0"),
+    ("License :: OSI Approved :: BSD License", "BSD"),
+    (
+        "License :: OSI Approved :: CEA CNRS Inria Logiciel Libre License, version 2.1 (CeCILL-2.1)",
+        "CECILL-2.1",
+    ),
+    (
+        "License :: OSI Approved :: Common Development and Distribution License 1.0 (CDDL-1.0)",
+        "CDDL-1.0",
+    ),
+    ("License :: OSI Approved :: Common Public License", "CPL-1.0"),
+    ("License :: OSI Approved :: Eclipse Public License 1.0 (EPL-1.0)", "EPL-1.0"),
+    ("License :: OSI Approved :: Eclipse Public License 2.0 (EPL-2.0)", "EPL-2.0"),
+    ("License :: OSI Approved :: European Union Public Licence 1.1 (EUPL 1.1)", "EUPL-1.1"),
+    ("License :: OSI Approved :: European Union Public Licence 1.2 (EUPL 1.2)", "EUPL-1.2"),
+    ("License :: OSI Approved :: GNU Affero General Public License v3", "AGPL-3.0-only"),
+    (
+        "License :: OSI Approved :: GNU Affero General Public License v3 or later (AGPLv3+)",
+        "AGPL-3.0-or-later",
+    ),
+    ("License :: OSI Approved :: GNU General Public License (GPL)", "GPL"),
+    ("License :: OSI Approved :: GNU General Public License v2 (GPLv2)", "GPL-2.0-only"),
+    (
+        "License :: OSI Approved :: GNU General Public License v2 or later (GPLv2+)",
+        "GPL-2.0-or-later",
+    ),
+    ("License :: OSI Approved :: GNU General Public License v3 (GPLv3)", "GPL-3.0-only"),
+    (
+        "License :: OSI Approved :: GNU General Public License v3 or later (GPLv3+)",
+        "GPL-3.0-or-later",
+    ),
+    (
+        "License :: OSI Approved :: GNU Lesser General Public License v2 (LGPLv2)",
+        "LGPL-2.0-only",
+    ),
+    (
+        "License :: OSI Approved :: GNU Lesser General Public License v2 or later (LGPLv2+)",
+        "LGPL-2.0-or-later",
+    ),
+    (
+        "License :: OSI Approved :: GNU Lesser General Public License v3 (LGPLv3)",
+        "LGPL-3.0-only",
+    ),
+    (
+        "License :: OSI Approved :: GNU Lesser General Public License v3 or later (LGPLv3+)",
+        "LGPL-3.0-or-later",
+    ),
+    ("License :: OSI Approved :: GNU Library or Lesser General Public License (LGPL)", "LGPL"),
+    ("License :: OSI Approved :: Historical Permission Notice and Disclaimer (HPND)", "HPND"),
+    ("License :: OSI Approved :: IBM Public License", "IPL-1.0"),
+    ("License :: OSI Approved :: ISC License (ISCL)", "ISC"),
+    ("License :: OSI Approved :: MIT License", "MIT"),
+    ("License :: OSI Approved :: MIT No Attribution License (MIT-0)", "MIT-0"),
+    ("License :: OSI Approved :: Mozilla Public License 1.0 (MPL)", "MPL-1.0"),
+    ("License :: OSI Approved :: Mozilla Public License 1.1 (MPL 1.1)", "MPL-1.1"),
+    ("License :: OSI Approved :: Mozilla Public License 2.0 (MPL 2.0)", "MPL-2.0"),
+    (
+        "License :: OSI Approved :: Mulan Permissive Software License v2 (MulanPSL-2.0)",
+        "MulanPSL-2.0",
+    ),
+    ("License :: OSI Approved :: Open Software License 3.0 (OSL-3.0)", "OSL-3.0"),
+    ("License :: OSI Approved :: PostgreSQL License", "PostgreSQL"),
+    ("License :: OSI Approved :: Python Software Foundation License", "PSF-2.0"),
+    ("License :: OSI Approved :: Sleepycat License", "Sleepycat"),
+    ("License :: OSI Approved :: The Unlicense (Unlicense)", "Unlicense"),
+    ("License :: OSI Approved :: Universal Permissive License (UPL)", "UPL-1.0"),
+    (
+        "License :: OSI Approved :: University of Illinois/NCSA Open Source License",
+        "NCSA",
+    ),
+    ("License :: OSI Approved :: W3C License", "W3C"),
+    ("License :: OSI Approved :: zlib/libpng License", "Zlib"),
+    ("License :: OSI Approved :: Zope Public License", "ZPL-2.1"),
+];
+
+/// License families named by the unversioned classifiers, which are not SPDX
+/// ids and do not tell which license of the family is meant.
+const FAMILIES: &[&str] = &["Apache", "Artistic", "BSD", "GPL", "LGPL"];
+
 #[derive(Deserialize, PartialEq, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct PyprojectToml {
@@ -14,13 +105,29 @@ pub struct PyprojectToml {
 #[serde(rename_all = "camelCase")]
 pub struct Project {
     license: Option<License>,
+    #[serde(rename = "license-files")]
+    license_files: Option<Vec<String>>,
+    authors: Option<Vec<Author>>,
+    classifiers: Option<Vec<String>>,
 }
 
 #[derive(Deserialize, PartialEq, Debug)]
 #[serde(rename_all = "camelCase")]
-pub struct License {
-    file: Option<String>,
-    text: Option<String>,
+pub struct Author {
+    name: Option<String>,
+    email: Option<String>,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(untagged)]
+pub enum License {
+    /// SPDX license expression, see https://peps.python.org/pep-0639/
+    Expression(String),
+    /// `{ text = ... }` or `{ file = ... }` of PEP 621
+    Table {
+        file: Option<String>,
+        text: Option<String>,
+    },
 }
 
 #[derive(Deserialize, PartialEq, Debug)]
@@ -33,19 +140,132 @@ pub struct Tool {
 #[serde(rename_all = "camelCase")]
 pub struct Poetry {
     license: Option<String>,
+    authors: Option<Vec<String>>,
+    classifiers: Option<Vec<String>>,
+}
+
+impl PyprojectToml {
+    /// The license declared in the `license` field.
+    fn declared_license(&self) -> Option<String> {
+        let project = self.project.as_ref();
+        let poetry = self.tool.as_ref().and_then(|tool| tool.poetry.as_ref());
+        let license = match project.and_then(|project| project.license.as_ref()) {
+            Some(License::Expression(expression)) => Some(expression),
+            Some(License::Table { text, .. }) => text.as_ref(),
+            None => None,
+        };
+        license
+            .or_else(|| poetry.and_then(|poetry| poetry.license.as_ref()))
+            .cloned()
+    }
+
+    /// The licenses of the `License ::` classifiers.
+    fn classifier_licenses(&self) -> Vec<&'static str> {
+        let project = self
+            .project
+            .as_ref()
+            .and_then(|project| project.classifiers.as_ref());
+        let poetry = self
+            .tool
+            .as_ref()
+            .and_then(|tool| tool.poetry.as_ref())
+            .and_then(|poetry| poetry.classifiers.as_ref());
+        let mut licenses = Vec::new();
+        for classifier in project.into_iter().chain(poetry).flatten() {
+            let license = CLASSIFIERS
+                .iter()
+                .find(|(name, _)| name == classifier)
+                .map(|(_, license)| *license);
+            if let Some(license) = license.filter(|license| !licenses.contains(license)) {
+                licenses.push(license);
+            }
+        }
+        licenses
+    }
 }
 
 impl Manifest for PyprojectToml {
     fn license(self) -> Result<String, ManifestError> {
-        self.project
-            .and_then(|project| project.license)
-            .and_then(|license| license.text)
-            .or_else(|| {
-                self.tool
-                    .and_then(|tool| tool.poetry)
-                    .and_then(|poetry| poetry.license)
-            })
-            .ok_or(ManifestError::LicenseNotFound)
+        if let Some(license) = self.declared_license() {
+            return Ok(license);
+        }
+        // Several license classifiers usually mean a choice between them.
+        let classifiers = self.classifier_licenses();
+        let is_family = |license: &&str| FAMILIES.contains(license);
+        if classifiers.is_empty() || classifiers.iter().any(is_family) {
+            return Err(ManifestError::LicenseNotFound);
+        }
+        Ok(classifiers.join(" OR "))
+    }
+
+    fn warnings(&self) -> Vec<String> {
+        let classifiers = self.classifier_licenses();
+        if classifiers.is_empty() {
+            return Vec::new();
+        }
+        let Some(declared) = self.declared_license() else {
+            return classifiers
+                .iter()
+                .filter(|license| FAMILIES.contains(license))
+                .map(|family| {
+                    format!(
+                        "The license classifier {family} of {} does not tell which {family} license it is, declare it in the license field.",
+                        Self::filename()
+                    )
+                })
+                .collect();
+        };
+        let declared_ids: Vec<String> = match Expression::parse(&declared) {
+            Ok(expression) => expression
+                .licenses()
+                .iter()
+                .map(|item| item.license.to_string())
+                .collect(),
+            Err(_) => vec![declared.clone()],
+        };
+        let is_classified = |id: &String| classifiers.iter().any(|c| may_be_same(id, c));
+        let is_declared = |c: &&str| declared_ids.iter().any(|id| may_be_same(id, c));
+        if declared_ids.iter().all(is_classified) && classifiers.iter().all(is_declared) {
+            return Vec::new();
+        }
+        vec![format!(
+            "The license classifiers of {} ({}) disagree with its license field ({declared}).",
+            Self::filename(),
+            classifiers.join(", ")
+        )]
+    }
+
+    fn license_file(&self) -> Option<String> {
+        match self.project.as_ref()?.license.as_ref()? {
+            License::Table { file, .. } => file.clone(),
+            License::Expression(_) => None,
+        }
+    }
+
+    fn license_files(&self) -> Option<Vec<String>> {
+        self.project.as_ref()?.license_files.clone()
+    }
+
+    fn authors(&self) -> Vec<String> {
+        let project = self
+            .project
+            .as_ref()
+            .and_then(|project| project.authors.as_ref())
+            .map(|authors| {
+                // An author may only have an email, which is no name.
+                authors
+                    .iter()
+                    .filter_map(|author| author.name.clone())
+                    .collect()
+            });
+        let poetry = || {
+            self.tool
+                .as_ref()
+                .and_then(|tool| tool.poetry.as_ref())
+                .and_then(|poetry| poetry.authors.as_ref())
+                .map(|authors| authors.iter().map(|author| author_name(author)).collect())
+        };
+        project.or_else(poetry).unwrap_or_default()
     }
 
     fn filename() -> String {
@@ -55,6 +275,17 @@ impl Manifest for PyprojectToml {
     fn from_str(text: &str) -> Result<Self, ManifestError> {
         toml::from_str(text).map_err(|_| ManifestError::ParseError)
     }
+
+    fn set_license(text: &str, license: &str) -> Result<String, ManifestError> {
+        set_toml_string(text, &["project", "license"], license)
+            .or_else(|_| set_toml_string(text, &["project", "license", "text"], license))
+            .or_else(|_| set_toml_string(text, &["tool", "poetry", "license"], license))
+            .or_else(|_| {
+                // A license file is replaced by the expression, as PEP 639 wants.
+                let tables: &[&[&str]] = &[&["project"], &["tool", "poetry"]];
+                insert_toml_string(text, tables, "license", &[], license)
+            })
+    }
 }
 
 #[cfg(test)]
@@ -92,11 +323,27 @@ mod tests {
                 project: None,
                 tool: Some(Tool {
                     poetry: Some(Poetry {
-                        license: Some(String::from("GPL-3.0"))
+                        license: Some(String::from("GPL-3.0")),
+                        authors: Some(vec![String::from("Nyakku Shigure <sigure.qaq@gmail.com>")]),
+                        classifiers: Some(
+                            [
+                                "Environment :: Console",
+                                "Operating System :: OS Independent",
+                                "License :: OSI Approved :: GNU General Public License v3 (GPLv3)",
+                                "Programming Language :: Python",
+                                "Programming Language :: Python :: 3",
+                                "Programming Language :: Python :: 3.9",
+                                "Programming Language :: Python :: 3.10",
+                                "Programming Language :: Python :: Implementation :: CPython",
+                            ]
+                            .map(String::from)
+                            .to_vec()
+                        ),
                     })
                 })
             }
         );
+        assert_eq!(lic.authors(), vec![String::from("Nyakku Shigure")]);
         assert_eq!(lic.license(), Ok(String::from("GPL-3.0")));
     }
 
@@ -132,14 +379,37 @@ mod tests {
             lic,
             PyprojectToml {
                 project: Some(Project {
-                    license: Some(License {
+                    license: Some(License::Table {
                         file: None,
                         text: Some(String::from("GPLv3"))
-                    })
+                    }),
+                    license_files: None,
+                    authors: Some(vec![Author {
+                        name: Some(String::from("Nyakku Shigure")),
+                        email: Some(String::from("sigure.qaq@gmail.com")),
+                    }]),
+                    classifiers: Some(
+                        [
+                            "Environment :: Console",
+                            "Operating System :: OS Independent",
+                            "License :: OSI Approved :: GNU General Public License v3 (GPLv3)",
+                            "Programming Language :: Python",
+                            "Programming Language :: Python :: 3",
+                            "Programming Language :: Python :: 3.8",
+                            "Programming Language :: Python :: 3.9",
+                            "Programming Language :: Python :: 3.10",
+                            "Programming Language :: Python :: 3.11",
+                            "Programming Language :: Python :: Implementation :: CPython",
+                        ]
+                        .map(String::from)
+                        .to_vec()
+                    ),
                 }),
                 tool: None
             }
         );
+        assert_eq!(lic.authors(), vec![String::from("Nyakku Shigure")]);
+        assert_eq!(lic.warnings(), Vec::<String>::new());
         assert_eq!(lic.license(), Ok(String::from("GPLv3")));
     }
 
@@ -153,13 +423,96 @@ mod tests {
         assert_eq!(
             lic,
             PyprojectToml {
-                project: Some(Project { license: None }),
+                project: Some(Project {
+                    license: None,
+                    license_files: None,
+                    authors: None,
+                    classifiers: None,
+                }),
                 tool: None
             }
         );
         assert_eq!(lic.license(), Err(ManifestError::LicenseNotFound));
     }
 
+    #[test]
+    fn test_pyproject_toml_license_file() {
+        let data = r#"
+        [project]
+        name = "lic"
+        license = { file = "LICENSE" }
+        "#;
+        let lic = PyprojectToml::from_str(data).unwrap();
+        assert_eq!(lic.license_file(), Some(String::from("LICENSE")));
+        assert_eq!(lic.license(), Err(ManifestError::LicenseNotFound));
+    }
+
+    #[test]
+    fn test_pyproject_toml_pep639() {
+        let data = r#"
+        [project]
+        name = "lic"
+        license = "MIT OR Apache-2.0"
+        license-files = ["LICEN[CS]E*", "AUTHORS*"]
+        "#;
+        let lic = PyprojectToml::from_str(data).unwrap();
+        assert_eq!(
+            lic.license_files(),
+            Some(vec![String::from("LICEN[CS]E*"), String::from("AUTHORS*")])
+        );
+        assert_eq!(lic.license(), Ok(String::from("MIT OR Apache-2.0")));
+        assert_eq!(
+            PyprojectToml::set_license(data, "MIT"),
+            Ok(data.replace("\"MIT OR Apache-2.0\"", "\"MIT\""))
+        );
+    }
+
+    #[test]
+    fn test_pyproject_toml_classifiers() {
+        let data = r#"
+        [project]
+        name = "lic"
+        classifiers = [
+            "License :: OSI Approved :: MIT License",
+            "License :: OSI Approved :: Apache Software License",
+            "Programming Language :: Python :: 3",
+        ]
+        "#;
+        let lic = PyprojectToml::from_str(data).unwrap();
+        assert_eq!(
+            lic.warnings(),
+            vec![String::from(
+                "The license classifier Apache of pyproject.toml does not tell which Apache license it is, declare it in the license field."
+            )]
+        );
+        assert_eq!(lic.license(), Err(ManifestError::LicenseNotFound));
+
+        let data = r#"
+        [project]
+        classifiers = [
+            "License :: OSI Approved :: MIT License",
+            "License :: OSI Approved :: GNU General Public License v3 (GPLv3)",
+        ]
+        "#;
+        let lic = PyprojectToml::from_str(data).unwrap();
+        assert_eq!(lic.warnings(), Vec::<String>::new());
+        assert_eq!(lic.license(), Ok(String::from("MIT OR GPL-3.0-only")));
+
+        let data = r#"
+        [tool.poetry]
+        license = "MIT"
+        classifiers = ["License :: OSI Approved :: BSD License"]
+        "#;
+        let lic = PyprojectToml::from_str(data).unwrap();
+        assert_eq!(
+            lic.warnings(),
+            vec![String::from(
+                "The license classifiers of pyproject.toml (BSD) disagree with its license field (MIT)."
+            )]
+        );
+        assert_eq!(lic.license(), Ok(String::from("MIT")));
+    }
+
     #[test]
     fn test_pyproject_toml_parse_error() {
         let data = r#"
@@ -170,4 +523,23 @@ mod tests {
         let lic = PyprojectToml::from_str(data);
         assert_eq!(lic, Err(ManifestError::ParseError));
     }
+
+    #[test]
+    fn test_pyproject_toml_set_license() {
+        let data = "[tool.poetry]\nname = \"yutto\"\nlicense = \"GPL-3.0\"\n";
+        assert_eq!(
+            PyprojectToml::set_license(data, "GPL-3.0-only"),
+            Ok("[tool.poetry]\nname = \"yutto\"\nlicense = \"GPL-3.0-only\"\n".into())
+        );
+        let data = "[project]\nlicense = { text = \"GPL-3.0\" }\n";
+        assert_eq!(
+            PyprojectToml::set_license(data, "GPL-3.0-only"),
+            Ok("[project]\nlicense = { text = \"GPL-3.0-only\" }\n".into())
+        );
+        let data = "[project]\nname = \"lic\"\nlicense = { file = \"LICENSE\" }\n";
+        assert_eq!(
+            PyprojectToml::set_license(data, "MIT"),
+            Ok("[project]\nname = \"lic\"\nlicense = \"MIT\"\n".into())
+        );
+    }
 }
@@ -1,24 +1,192 @@
-use super::base::{Manifest, ManifestError};
+use super::base::{
+    author_name, find_members, insert_toml_string, set_toml_string, Manifest, ManifestError,
+};
 use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
 
 #[derive(Deserialize, PartialEq, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct CargoToml {
     // https://doc.rust-lang.org/cargo/reference/manifest.html
     package: Option<Package>,
+    // https://doc.rust-lang.org/cargo/reference/workspaces.html
+    workspace: Option<Workspace>,
+}
+
+/// A field of a workspace member, either set or inherited from the workspace
+/// with `field.workspace = true`.
+#[derive(Deserialize, PartialEq, Debug, Clone)]
+#[serde(untagged)]
+pub enum Inheritable<T> {
+    Value(T),
+    Workspace { workspace: bool },
 }
 
 #[derive(Deserialize, PartialEq, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Package {
+    license: Option<Inheritable<String>>,
+    #[serde(rename = "license-file")]
+    license_file: Option<Inheritable<String>>,
+    authors: Option<Inheritable<Vec<String>>>,
+    /// Path to the workspace root, if it is not the closest parent workspace
+    workspace: Option<String>,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Workspace {
+    members: Option<Vec<String>>,
+    exclude: Option<Vec<String>>,
+    package: Option<WorkspacePackage>,
+}
+
+/// The `[workspace.package]` fields the members may inherit.
+#[derive(Deserialize, PartialEq, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspacePackage {
     license: Option<String>,
+    #[serde(rename = "license-file")]
+    license_file: Option<String>,
+    authors: Option<Vec<String>>,
+}
+
+impl<T> Inheritable<T> {
+    fn value(&self) -> Option<&T> {
+        match self {
+            Inheritable::Value(value) => Some(value),
+            Inheritable::Workspace { .. } => None,
+        }
+    }
+
+    fn is_inherited(&self) -> bool {
+        matches!(self, Inheritable::Workspace { workspace: true })
+    }
+}
+
+/// Replaces an inherited `field` by the value of the workspace.
+fn inherit<T>(field: &mut Option<Inheritable<T>>, value: Option<T>) {
+    if field.as_ref().is_some_and(Inheritable::is_inherited) {
+        *field = value.map(Inheritable::Value);
+    }
+}
+
+impl CargoToml {
+    fn workspace_package(&self) -> Option<&WorkspacePackage> {
+        self.workspace.as_ref()?.package.as_ref()
+    }
+
+    /// Whether the crate inherits its license from the workspace.
+    pub fn inherits_license(&self) -> bool {
+        self.package
+            .as_ref()
+            .and_then(|package| package.license.as_ref())
+            .is_some_and(Inheritable::is_inherited)
+    }
+
+    fn inherits(&self) -> bool {
+        self.package.as_ref().is_some_and(|package| {
+            package
+                .license
+                .as_ref()
+                .is_some_and(Inheritable::is_inherited)
+                || package
+                    .license_file
+                    .as_ref()
+                    .is_some_and(Inheritable::is_inherited)
+                || package
+                    .authors
+                    .as_ref()
+                    .is_some_and(Inheritable::is_inherited)
+        })
+    }
+
+    /// Fills in the fields inherited from the workspace `root`, a path
+    /// relative to this manifest.
+    fn inherit(&mut self, root: &Path, workspace: Option<&WorkspacePackage>) {
+        let Some(package) = self.package.as_mut() else {
+            return;
+        };
+        let default = WorkspacePackage::default();
+        let workspace = workspace.unwrap_or(&default);
+        inherit(&mut package.license, workspace.license.clone());
+        // The license file of the workspace is relative to its root.
+        let license_file = workspace
+            .license_file
+            .as_ref()
+            .map(|file| root.join(file).to_string_lossy().to_string());
+        inherit(&mut package.license_file, license_file);
+        inherit(&mut package.authors, workspace.authors.clone());
+    }
+
+    /// Finds the workspace the crate in `dir` belongs to, or is the root of,
+    /// returning its root relative to `dir` and its manifest.
+    pub fn find_workspace(dir: &Path) -> Result<(PathBuf, CargoToml), ManifestError> {
+        let read = |root: &Path| {
+            fs::read_to_string(dir.join(root).join(Self::filename()))
+                .ok()
+                .map(|text| Self::from_str(&text))
+        };
+        let explicit = read(Path::new(""))
+            .and_then(Result::ok)
+            .and_then(|manifest| manifest.package?.workspace);
+        if let Some(root) = explicit {
+            let root = PathBuf::from(root);
+            return match read(&root) {
+                Some(Ok(manifest)) if manifest.workspace.is_some() => Ok((root, manifest)),
+                Some(Err(e)) => Err(e),
+                _ => Err(ManifestError::WorkspaceNotFound),
+            };
+        }
+        let absolute = Path::new(".")
+            .join(dir)
+            .canonicalize()
+            .map_err(|_| ManifestError::WorkspaceNotFound)?;
+        let mut root = PathBuf::new();
+        for _ in absolute.ancestors() {
+            match read(&root) {
+                Some(Ok(manifest)) if manifest.workspace.is_some() => return Ok((root, manifest)),
+                Some(Err(e)) => return Err(e),
+                _ => root.push(".."),
+            }
+        }
+        Err(ManifestError::WorkspaceNotFound)
+    }
 }
 
 impl Manifest for CargoToml {
     fn license(self) -> Result<String, ManifestError> {
-        self.package
-            .and_then(|package| package.license)
-            .ok_or(ManifestError::LicenseNotFound)
+        match &self.package {
+            Some(package) => package
+                .license
+                .as_ref()
+                .and_then(Inheritable::value)
+                .cloned(),
+            // A virtual manifest, only the workspace has a license.
+            None => self
+                .workspace_package()
+                .and_then(|package| package.license.clone()),
+        }
+        .ok_or(ManifestError::LicenseNotFound)
+    }
+
+    fn license_file(&self) -> Option<String> {
+        match &self.package {
+            Some(package) => package.license_file.as_ref()?.value().cloned(),
+            None => self.workspace_package()?.license_file.clone(),
+        }
+    }
+
+    fn authors(&self) -> Vec<String> {
+        match &self.package {
+            Some(package) => package.authors.as_ref().and_then(Inheritable::value),
+            None => self
+                .workspace_package()
+                .and_then(|package| package.authors.as_ref()),
+        }
+        .map(|authors| authors.iter().map(|author| author_name(author)).collect())
+        .unwrap_or_default()
     }
 
     fn filename() -> String {
@@ -28,6 +196,46 @@ impl Manifest for CargoToml {
     fn from_str(text: &str) -> Result<Self, ManifestError> {
         toml::from_str(text).map_err(|_| ManifestError::ParseError)
     }
+
+    fn resolve(mut self, dir: &Path) -> Result<Self, ManifestError> {
+        if !self.inherits() {
+            return Ok(self);
+        }
+        if self.workspace.is_some() {
+            let workspace = self.workspace.as_mut().and_then(|w| w.package.take());
+            self.inherit(Path::new(""), workspace.as_ref());
+            self.workspace.as_mut().unwrap().package = workspace;
+            return Ok(self);
+        }
+        let (root, workspace) = Self::find_workspace(dir)?;
+        self.inherit(&root, workspace.workspace_package());
+        Ok(self)
+    }
+
+    /// The directories of the member crates of the workspace in `root`,
+    /// relative to `root`, the root itself being empty if it is a crate.
+    fn members(&self, root: &Path) -> Result<Vec<PathBuf>, ManifestError> {
+        let Some(workspace) = &self.workspace else {
+            return Ok(Vec::new());
+        };
+        let members = workspace.members.as_deref().unwrap_or_default();
+        let exclude = workspace.exclude.as_deref().unwrap_or_default();
+        let mut dirs = Vec::new();
+        if self.package.is_some() {
+            dirs.push(PathBuf::new());
+        }
+        dirs.extend(find_members(root, members, exclude, &Self::filename())?);
+        Ok(dirs)
+    }
+
+    fn set_license(text: &str, license: &str) -> Result<String, ManifestError> {
+        set_toml_string(text, &["package", "license"], license)
+            .or_else(|_| set_toml_string(text, &["workspace", "package", "license"], license))
+            .or_else(|_| {
+                let tables: &[&[&str]] = &[&["package"], &["workspace", "package"]];
+                insert_toml_string(text, tables, "license", &["license-file"], license)
+            })
+    }
 }
 
 #[cfg(test)]
@@ -52,10 +260,15 @@ mod tests {
             lic,
             CargoToml {
                 package: Some(Package {
-                    license: Some(String::from("MIT"))
-                })
+                    license: Some(Inheritable::Value(String::from("MIT"))),
+                    license_file: None,
+                    authors: Some(Inheritable::Value(vec![String::from("Nyakku Shigure")])),
+                    workspace: None,
+                }),
+                workspace: None,
             }
         );
+        assert_eq!(lic.authors(), vec![String::from("Nyakku Shigure")]);
         assert_eq!(lic.license(), Ok(String::from("MIT")));
     }
 
@@ -68,12 +281,114 @@ mod tests {
         assert_eq!(
             lic,
             CargoToml {
-                package: Some(Package { license: None })
+                package: Some(Package {
+                    license: None,
+                    license_file: None,
+                    authors: None,
+                    workspace: None,
+                }),
+                workspace: None,
             }
         );
         assert_eq!(lic.license(), Err(ManifestError::LicenseNotFound));
     }
 
+    #[test]
+    fn test_cargo_toml_license_file() {
+        let data = r#"
+        [package]
+        name = "lic"
+        license-file = "LICENSE.txt"
+        "#;
+        let lic = CargoToml::from_str(data).unwrap();
+        assert_eq!(lic.license_file(), Some(String::from("LICENSE.txt")));
+        assert_eq!(lic.license(), Err(ManifestError::LicenseNotFound));
+    }
+
+    #[test]
+    fn test_cargo_toml_workspace() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |path: &str, text: &str| {
+            let path = dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, text).unwrap();
+        };
+        write(
+            "Cargo.toml",
+            r#"
+            [workspace]
+            members = ["crates/*"]
+            exclude = ["crates/skipped"]
+
+            [workspace.package]
+            license = "MIT OR Apache-2.0"
+            authors = ["Nyakku Shigure <sigure.qaq@gmail.com>"]
+            "#,
+        );
+        let member = r#"
+            [package]
+            name = "a"
+            license.workspace = true
+            authors.workspace = true
+            "#;
+        write("crates/a/Cargo.toml", member);
+        write(
+            "crates/b/Cargo.toml",
+            "[package]\nname = \"b\"\nlicense = \"MIT\"\n",
+        );
+        write(
+            "crates/skipped/Cargo.toml",
+            "[package]\nname = \"skipped\"\n",
+        );
+        write("crates/not-a-crate/README.md", "");
+
+        let root = CargoToml::from_str(&fs::read_to_string(dir.path().join("Cargo.toml")).unwrap())
+            .unwrap()
+            .resolve(dir.path())
+            .unwrap();
+        assert_eq!(
+            root.members(dir.path()),
+            Ok(vec![PathBuf::from("crates/a"), PathBuf::from("crates/b")])
+        );
+        assert_eq!(root.license(), Ok(String::from("MIT OR Apache-2.0")));
+
+        let a = dir.path().join("crates/a");
+        let lic = CargoToml::from_str(member).unwrap();
+        assert_eq!(lic.license_file(), None);
+        let lic = lic.resolve(&a).unwrap();
+        assert_eq!(lic.authors(), vec![String::from("Nyakku Shigure")]);
+        assert_eq!(lic.license(), Ok(String::from("MIT OR Apache-2.0")));
+        assert_eq!(
+            CargoToml::find_workspace(&a).map(|(root, _)| root),
+            Ok(PathBuf::from("../.."))
+        );
+
+        let lonely = tempfile::tempdir().unwrap();
+        assert_eq!(
+            CargoToml::from_str(member).unwrap().resolve(lonely.path()),
+            Err(ManifestError::WorkspaceNotFound)
+        );
+    }
+
+    #[test]
+    fn test_cargo_toml_set_license() {
+        let text = "[workspace.package]\nlicense = \"GPL-3.0\"\n";
+        assert_eq!(
+            CargoToml::set_license(text, "GPL-3.0-only"),
+            Ok("[workspace.package]\nlicense = \"GPL-3.0-only\"\n".into())
+        );
+        let text = "[package]\nname = \"lic\"\n\n[dependencies]\n";
+        assert_eq!(
+            CargoToml::set_license(text, "MIT"),
+            Ok("[package]\nname = \"lic\"\nlicense = \"MIT\"\n\n[dependencies]\n".into())
+        );
+        let text = "[package]\nname = \"lic\"\nlicense-file = \"LICENSE\"\n";
+        assert_eq!(
+            CargoToml::set_license(text, "MIT"),
+            Ok("[package]\nname = \"lic\"\nlicense = \"MIT\"\n".into())
+        );
+    }
+
     #[test]
     fn test_cargo_toml_parse_error() {
         let data = r"
@@ -0,0 +1,309 @@
+//! Mapping the license names found in real manifests, e.g. `GPLv3`,
+//! `Apache 2` or `MIT License`, to SPDX license ids.
+
+use super::list::Licenses;
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// Names which can't be derived from the ids and names of the license list,
+/// with the id they most likely mean and the other ids they may mean.
+const ALIASES: &[(&str, &str, &[&str])] = &[
+    ("Expat", "MIT", &[]),
+    ("BSD", "BSD-3-Clause", &["BSD-2-Clause"]),
+    ("BSD 3", "BSD-3-Clause", &[]),
+    ("New BSD", "BSD-3-Clause", &[]),
+    ("Modified BSD", "BSD-3-Clause", &[]),
+    ("Revised BSD", "BSD-3-Clause", &[]),
+    ("BSD 2", "BSD-2-Clause", &[]),
+    ("Simplified BSD", "BSD-2-Clause", &[]),
+    ("FreeBSD", "BSD-2-Clause", &[]),
+    ("Apache", "Apache-2.0", &["Apache-1.1"]),
+    ("Apache Software License", "Apache-2.0", &["Apache-1.1"]),
+    ("ASL 2.0", "Apache-2.0", &[]),
+    (
+        "GPL",
+        "GPL-3.0-or-later",
+        &["GPL-3.0-only", "GPL-2.0-or-later", "GPL-2.0-only"],
+    ),
+    (
+        "LGPL",
+        "LGPL-3.0-or-later",
+        &["LGPL-3.0-only", "LGPL-2.1-or-later", "LGPL-2.1-only"],
+    ),
+    ("AGPL", "AGPL-3.0-or-later", &["AGPL-3.0-only"]),
+    ("MPL", "MPL-2.0", &["MPL-1.1"]),
+    ("EPL", "EPL-2.0", &["EPL-1.0"]),
+    ("PSF", "PSF-2.0", &[]),
+    ("PSFL", "PSF-2.0", &[]),
+    ("Python Software Foundation License", "PSF-2.0", &[]),
+    ("Boost", "BSL-1.0", &[]),
+    ("Boost Software License", "BSL-1.0", &[]),
+    ("CC0", "CC0-1.0", &[]),
+    ("Artistic", "Artistic-2.0", &["Artistic-1.0"]),
+];
+
+/// Spelled out license families, replaced by their usual abbreviation.
+const ABBREVIATIONS: &[(&str, &str)] = &[
+    ("gnulessergeneralpublic", "lgpl"),
+    ("lessergeneralpublic", "lgpl"),
+    ("gnulibrarygeneralpublic", "lgpl"),
+    ("librarygeneralpublic", "lgpl"),
+    ("gnuafferogeneralpublic", "agpl"),
+    ("afferogeneralpublic", "agpl"),
+    ("gnugeneralpublic", "gpl"),
+    ("generalpublic", "gpl"),
+    ("gnulgpl", "lgpl"),
+    ("gnuagpl", "agpl"),
+    ("gnugpl", "gpl"),
+    ("mozillapublic", "mpl"),
+    ("eclipsepublic", "epl"),
+    ("anylater", "orlater"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Confidence {
+    /// The name may mean several licenses, the most likely one is picked
+    Low,
+    /// The name means a single license
+    High,
+}
+
+/// A SPDX license id a license name has been mapped to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Normalized {
+    pub id: String,
+    pub confidence: Confidence,
+    /// Other licenses an ambiguous name may mean
+    pub alternatives: Vec<String>,
+}
+
+/// Reduces a license name to what tells licenses apart, so that e.g.
+/// `GPLv3`, `GPL-3.0` and `GNU General Public License v3.0` are the same.
+fn key(name: &str) -> String {
+    static VERSION: OnceLock<Regex> = OnceLock::new();
+    let version = VERSION.get_or_init(|| Regex::new(r"^([a-z]*)v(\d)").unwrap());
+    let name = name
+        .to_lowercase()
+        .replace("licence", "license")
+        .replace('+', " or later ");
+    let mut key = String::new();
+    for word in name.split(|c: char| !c.is_ascii_alphanumeric() && c != '.') {
+        let word = word.trim_matches('.');
+        if matches!(word, "" | "the" | "license" | "version") {
+            continue;
+        }
+        let word = version.replace(word, "$1$2");
+        key += word.strip_suffix(".0").unwrap_or(&word);
+    }
+    for (spelled, abbreviation) in ABBREVIATIONS {
+        key = key.replace(spelled, abbreviation);
+    }
+    key
+}
+
+/// Whether two license names may mean the same license, e.g. `GPLv3` and
+/// `GPL-3.0-only`, or `BSD` and `BSD-3-Clause` through the aliases, but not
+/// `MIT` and `MIT-0`.
+pub fn may_be_same(a: &str, b: &str) -> bool {
+    let (a, b) = (meanings(a), meanings(b));
+    a.iter().any(|key| b.contains(key))
+}
+
+/// The keys of the licenses a name may mean, without the `only` and
+/// `or later` variants which a name without them may mean both.
+fn meanings(name: &str) -> Vec<String> {
+    let base = |key: String| match key
+        .strip_suffix("only")
+        .or_else(|| key.strip_suffix("orlater"))
+    {
+        Some(base) => base.to_string(),
+        None => key,
+    };
+    let key = key(name);
+    if key.is_empty() {
+        return Vec::new();
+    }
+    let mut meanings = vec![base(key.clone())];
+    for (alias, id, alternatives) in ALIASES {
+        if self::key(alias) == key {
+            let ids = std::iter::once(id).chain(alternatives.iter());
+            meanings.extend(ids.map(|id| base(self::key(id))));
+        }
+    }
+    meanings
+}
+
+impl Licenses {
+    /// Maps a license `name` which is not a SPDX license id to the id it most
+    /// likely means, e.g. `GPL-3.0-only` for `GPLv3` and `Apache-2.0` for
+    /// `Apache License, Version 2.0`.
+    pub fn normalize_name(&self, name: &str) -> Option<Normalized> {
+        let key = key(name);
+        if key.is_empty() {
+            return None;
+        }
+        let known = |id: &str| self.get_license(id).is_some();
+        if let Some((_, id, alternatives)) = ALIASES
+            .iter()
+            .find(|(alias, id, _)| self::key(alias) == key && known(id))
+        {
+            let alternatives: Vec<String> = alternatives
+                .iter()
+                .filter(|id| known(id))
+                .map(|id| id.to_string())
+                .collect();
+            return Some(Normalized {
+                id: id.to_string(),
+                confidence: if alternatives.is_empty() {
+                    Confidence::High
+                } else {
+                    Confidence::Low
+                },
+                alternatives,
+            });
+        }
+
+        let matching = |key: &str| -> Vec<String> {
+            let mut ids: Vec<String> = Vec::new();
+            for license in self
+                .licenses
+                .iter()
+                .filter(|lic| !lic.is_deprecated_license_id)
+            {
+                let id = &license.license_id;
+                if (self::key(id) == key || self::key(&license.name) == key) && !ids.contains(id) {
+                    ids.push(id.clone());
+                }
+            }
+            ids
+        };
+        let mut ids = matching(&key);
+        if ids.is_empty() {
+            // `GPLv3` may be `GPL-3.0-only` or `GPL-3.0-or-later`, the former
+            // being what the deprecated `GPL-3.0` meant.
+            ids = matching(&format!("{key}only"));
+            ids.extend(matching(&format!("{key}orlater")));
+        }
+        if ids.is_empty() {
+            return None;
+        }
+        let id = ids.remove(0);
+        Some(Normalized {
+            id,
+            confidence: if ids.is_empty() {
+                Confidence::High
+            } else {
+                Confidence::Low
+            },
+            alternatives: ids,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn licenses(ids: &[(&str, &str)]) -> Licenses {
+        let licenses: Vec<String> = ids
+            .iter()
+            .map(|(id, name)| {
+                format!(
+                    r#"{{
+                        "reference": "https://spdx.org/licenses/{id}.html",
+                        "isDeprecatedLicenseId": false,
+                        "detailsUrl": "https://spdx.org/licenses/{id}.json",
+                        "referenceNumber": 1,
+                        "name": "{name}",
+                        "licenseId": "{id}",
+                        "seeAlso": [],
+                        "isOsiApproved": true
+                    }}"#
+                )
+            })
+            .collect();
+        serde_json::from_str(&format!(
+            r#"{{ "licenseListVersion": "3.16", "licenses": [{}], "releaseDate": "2022-02-06" }}"#,
+            licenses.join(",")
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_key() {
+        assert_eq!(key("GPLv3"), "gpl3");
+        assert_eq!(
+            key("GNU General Public License v3.0 or later"),
+            "gpl3orlater"
+        );
+        assert_eq!(key("GPL-3.0+"), "gpl3orlater");
+        assert_eq!(key("Apache License, Version 2.0"), "apache2");
+        assert_eq!(key("LGPLv2.1"), "lgpl2.1");
+    }
+
+    #[test]
+    fn test_may_be_same() {
+        assert!(may_be_same("GPLv3", "GPL-3.0-only"));
+        assert!(may_be_same("BSD", "BSD-3-Clause"));
+        assert!(may_be_same("Apache License 2.0", "Apache-2.0"));
+        assert!(!may_be_same("LGPL-3.0-only", "GPL-3.0-only"));
+        assert!(!may_be_same("MIT", "Apache-2.0"));
+        assert!(!may_be_same("MIT", "MIT-0"));
+        assert!(!may_be_same("BSD-2-Clause", "BSD-2-Clause-Patent"));
+        assert!(!may_be_same("BSD", "0BSD"));
+    }
+
+    #[test]
+    fn test_normalize_name() {
+        let licenses = licenses(&[
+            ("MIT", "MIT License"),
+            ("Apache-1.1", "Apache License 1.1"),
+            ("Apache-2.0", "Apache License 2.0"),
+            ("BSD-2-Clause", "BSD 2-Clause \\\"Simplified\\\" License"),
+            (
+                "BSD-3-Clause",
+                "BSD 3-Clause \\\"New\\\" or \\\"Revised\\\" License",
+            ),
+            ("GPL-3.0-only", "GNU General Public License v3.0 only"),
+            (
+                "GPL-3.0-or-later",
+                "GNU General Public License v3.0 or later",
+            ),
+        ]);
+        let normalize = |name| licenses.normalize_name(name).unwrap();
+        let high = |id: &str| Normalized {
+            id: id.into(),
+            confidence: Confidence::High,
+            alternatives: Vec::new(),
+        };
+        assert_eq!(normalize("MIT License"), high("MIT"));
+        assert_eq!(normalize("Apache 2"), high("Apache-2.0"));
+        assert_eq!(normalize("BSD 3-Clause"), high("BSD-3-Clause"));
+        assert_eq!(normalize("GPLv3+"), high("GPL-3.0-or-later"));
+        assert_eq!(
+            normalize("GPLv3"),
+            Normalized {
+                id: "GPL-3.0-only".into(),
+                confidence: Confidence::Low,
+                alternatives: vec!["GPL-3.0-or-later".into()],
+            }
+        );
+        assert_eq!(
+            normalize("BSD"),
+            Normalized {
+                id: "BSD-3-Clause".into(),
+                confidence: Confidence::Low,
+                alternatives: vec!["BSD-2-Clause".into()],
+            }
+        );
+        assert_eq!(
+            normalize("Apache Software License"),
+            Normalized {
+                id: "Apache-2.0".into(),
+                confidence: Confidence::Low,
+                alternatives: vec!["Apache-1.1".into()],
+            }
+        );
+        assert_eq!(licenses.normalize_name("Proprietary"), None);
+    }
+}
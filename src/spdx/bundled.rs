@@ -0,0 +1,50 @@
+//! Snapshot of the SPDX license list embedded at compile time by the
+//! `bundled` feature. Every function returns `None` when the feature is off.
+
+#[cfg(feature = "bundled")]
+include!(concat!(env!("OUT_DIR"), "/bundled.rs"));
+
+/// Only available when no other version is pinned.
+#[cfg_attr(not(feature = "bundled"), allow(unused_variables))]
+pub fn licenses(pinned: Option<&str>) -> Option<&'static str> {
+    #[cfg(feature = "bundled")]
+    if pinned.map_or(true, |version| version == LICENSE_LIST_VERSION) {
+        return Some(LICENSES);
+    }
+    None
+}
+
+/// The bundled details only belong to the bundled license list version.
+#[cfg_attr(not(feature = "bundled"), allow(unused_variables))]
+pub fn details(version: &str, id: &str) -> Option<&'static str> {
+    #[cfg(feature = "bundled")]
+    if version == LICENSE_LIST_VERSION {
+        return DETAILS
+            .iter()
+            .find(|(license_id, _)| *license_id == id)
+            .map(|(_, details)| *details);
+    }
+    None
+}
+
+/// Only available when no other version is pinned.
+#[cfg_attr(not(feature = "bundled"), allow(unused_variables))]
+pub fn exceptions(pinned: Option<&str>) -> Option<&'static str> {
+    #[cfg(feature = "bundled")]
+    if pinned.map_or(true, |version| version == LICENSE_LIST_VERSION) {
+        return Some(EXCEPTIONS);
+    }
+    None
+}
+
+#[cfg_attr(not(feature = "bundled"), allow(unused_variables))]
+pub fn exception_details(version: &str, id: &str) -> Option<&'static str> {
+    #[cfg(feature = "bundled")]
+    if version == LICENSE_LIST_VERSION {
+        return EXCEPTION_DETAILS
+            .iter()
+            .find(|(exception_id, _)| *exception_id == id)
+            .map(|(_, details)| *details);
+    }
+    None
+}
@@ -0,0 +1,359 @@
+use super::bundled;
+use super::error::Error;
+use super::source::{check_list_version, list_version, LicenseSource, Resource};
+use async_trait::async_trait;
+use serde::de::IgnoredAny;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime};
+
+/// How long the cached `licenses.json` is considered fresh.
+pub const DEFAULT_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// On-disk cache in front of another [`LicenseSource`].
+///
+/// Layout under the cache directory:
+///
+/// - `licenses.json`: the latest fetched license list, refreshed after `ttl`
+/// - `exceptions.json`: the same for the license exceptions, when they are
+///   loaded without the license list
+/// - `<licenseListVersion>/licenses.json`, `<licenseListVersion>/exceptions.json`:
+///   the lists of that version, used when the version is pinned or known from
+///   the other list
+/// - `<licenseListVersion>/<id>.json`: license details
+/// - `<licenseListVersion>/exceptions/<id>.json`: license exception details
+///
+/// Everything under a version directory never expires since it is immutable
+/// for a given license list version, and nothing of another version is ever
+/// written into it: without a pinned version, the inner source serves the
+/// latest list, so it is checked to still serve the version in use first.
+pub struct Cache {
+    inner: Box<dyn LicenseSource>,
+    dir: PathBuf,
+    ttl: Duration,
+    offline: bool,
+    refresh: bool,
+    bundled: bool,
+    pinned: Option<String>,
+    list_version: OnceLock<String>,
+    /// Version of the lists the inner source serves, once fetched
+    source_version: OnceLock<String>,
+}
+
+impl Cache {
+    pub fn new(inner: Box<dyn LicenseSource>) -> Self {
+        Cache {
+            inner,
+            dir: Cache::default_dir(),
+            ttl: DEFAULT_TTL,
+            offline: false,
+            refresh: false,
+            bundled: false,
+            pinned: None,
+            list_version: OnceLock::new(),
+            source_version: OnceLock::new(),
+        }
+    }
+
+    /// `$XDG_CACHE_HOME/lic` (or the platform equivalent).
+    pub fn default_dir() -> PathBuf {
+        dirs::cache_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("lic")
+    }
+
+    pub fn dir(mut self, dir: PathBuf) -> Self {
+        self.dir = dir;
+        self
+    }
+
+    pub fn offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    pub fn refresh(mut self, refresh: bool) -> Self {
+        self.refresh = refresh;
+        self
+    }
+
+    /// Whether to use the snapshot of the `bundled` feature, if built with it.
+    pub fn bundled(mut self, bundled: bool) -> Self {
+        self.bundled = bundled;
+        self
+    }
+
+    /// Only use the license list of `version`, if any.
+    pub fn pin(mut self, version: Option<String>) -> Self {
+        if let Some(version) = &version {
+            let _ = self.list_version.set(version.clone());
+        }
+        self.pinned = version;
+        self
+    }
+
+    fn list_version(&self) -> &str {
+        self.list_version
+            .get()
+            .map(String::as_str)
+            .unwrap_or("unknown")
+    }
+
+    fn key(&self, resource: Resource<'_>) -> String {
+        match resource {
+            Resource::Licenses | Resource::Exceptions => match self.list_version.get() {
+                Some(version) => format!("{version}/{}", resource.list_data_path()),
+                None => resource.list_data_path(),
+            },
+            Resource::LicenseDetails(id) => format!("{}/{id}.json", self.list_version()),
+            Resource::ExceptionDetails(id) => {
+                format!("{}/exceptions/{id}.json", self.list_version())
+            }
+        }
+    }
+
+    fn snapshot(&self, resource: Resource<'_>) -> Option<&'static str> {
+        if !self.bundled {
+            return None;
+        }
+        match resource {
+            Resource::Licenses => bundled::licenses(self.list_version.get().map(String::as_str)),
+            Resource::LicenseDetails(id) => bundled::details(self.list_version(), id),
+            Resource::Exceptions => {
+                bundled::exceptions(self.list_version.get().map(String::as_str))
+            }
+            Resource::ExceptionDetails(id) => bundled::exception_details(self.list_version(), id),
+        }
+    }
+
+    /// Loads `resource` from the cache if it is still fresh, otherwise fetches
+    /// it from the inner source. A stale cache entry or the bundled snapshot
+    /// is used as a fallback when the network is unavailable or disabled.
+    async fn get(&self, resource: Resource<'_>) -> Result<String, Error> {
+        let key = self.key(resource);
+        let path = self.dir.join(&key);
+        let bundled = self.snapshot(resource);
+        // The version `resource` belongs to, pinned or of the list loaded first.
+        let known = self.list_version.get();
+        // With a bundled snapshot at hand the network is only used to refresh,
+        // so whatever was cached last time never expires.
+        let max_age = match resource {
+            _ if resource.is_list() && bundled.is_none() && known.is_none() => Some(self.ttl),
+            _ => None,
+        };
+        if !self.refresh {
+            if let Some(text) = read(&path, max_age).or(bundled.map(String::from)) {
+                return Ok(text);
+            }
+        }
+        let fallback = || read(&path, None).or(bundled.map(String::from));
+        if self.offline {
+            return fallback().ok_or(Error::Offline(key));
+        }
+        if let (None, Some(version)) = (&self.pinned, known) {
+            if let Err(e) = self.check_source(version).await {
+                return match e {
+                    Error::Outdated { .. } => Err(e),
+                    e => fallback().ok_or(e),
+                };
+            }
+        }
+        match self.inner.fetch(resource).await {
+            Ok(text) => {
+                serde_json::from_str::<IgnoredAny>(&text)?;
+                if resource.is_list() {
+                    match known {
+                        Some(version) if self.pinned.is_some() => {
+                            check_list_version(&text, version)?
+                        }
+                        Some(version) => {
+                            if let Err(Error::VersionMismatch { found, .. }) =
+                                check_list_version(&text, version)
+                            {
+                                let cached = version.clone();
+                                return Err(Error::Outdated { cached, found });
+                            }
+                        }
+                        // Also record it by version, so that it can be pinned later.
+                        None => {
+                            if let Some(version) = list_version(&text) {
+                                write(&self.dir.join(&version).join(&key), &text);
+                                let _ = self.source_version.set(version);
+                            }
+                        }
+                    }
+                }
+                write(&path, &text);
+                Ok(text)
+            }
+            Err(e) => fallback().ok_or(e),
+        }
+    }
+}
+
+impl Cache {
+    /// Checks that the unpinned inner source still serves the lists of
+    /// `version`, which may have been cached before a new version was
+    /// released. The new license list is cached for the next run.
+    async fn check_source(&self, version: &str) -> Result<(), Error> {
+        if self.source_version.get().is_none() {
+            let text = self.inner.fetch(Resource::Licenses).await?;
+            serde_json::from_str::<IgnoredAny>(&text)?;
+            let found = list_version(&text).unwrap_or_else(|| "unknown".into());
+            if found != version {
+                let key = Resource::Licenses.list_data_path();
+                write(&self.dir.join(&key), &text);
+                write(&self.dir.join(&found).join(&key), &text);
+            }
+            let _ = self.source_version.set(found);
+        }
+        match self.source_version.get() {
+            Some(found) if found != version => Err(Error::Outdated {
+                cached: version.into(),
+                found: found.clone(),
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[async_trait]
+impl LicenseSource for Cache {
+    async fn fetch(&self, resource: Resource<'_>) -> Result<String, Error> {
+        let text = self.get(resource).await?;
+        if resource.is_list() {
+            // Remember which license list version the details should be keyed by.
+            if let Some(version) = list_version(&text) {
+                let _ = self.list_version.set(version);
+            }
+        }
+        Ok(text)
+    }
+}
+
+fn read(path: &Path, max_age: Option<Duration>) -> Option<String> {
+    if let Some(max_age) = max_age {
+        let modified = fs::metadata(path).and_then(|meta| meta.modified()).ok()?;
+        let age = SystemTime::now()
+            .duration_since(modified)
+            .unwrap_or_default();
+        if age > max_age {
+            return None;
+        }
+    }
+    let text = fs::read_to_string(path).ok()?;
+    serde_json::from_str::<IgnoredAny>(&text).ok()?;
+    Some(text)
+}
+
+fn write(path: &Path, text: &str) {
+    // The cache is best-effort, failing to write it should never fail the command.
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    let _ = fs::write(path, text);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::source::FixtureSource;
+    use super::*;
+
+    const LICENSES: &str = r#"{"licenseListVersion": "3.16"}"#;
+
+    fn cache(dir: &Path, source: FixtureSource) -> Cache {
+        // Without the snapshot of the `bundled` feature, which would answer
+        // instead of `source`.
+        Cache::new(Box::new(source)).dir(dir.into()).bundled(false)
+    }
+
+    #[tokio::test]
+    async fn test_cache_miss() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = FixtureSource::default().with(Resource::Licenses, LICENSES);
+        let cache = cache(dir.path(), source);
+        assert_eq!(cache.fetch(Resource::Licenses).await.unwrap(), LICENSES);
+        assert!(dir.path().join("licenses.json").exists());
+    }
+
+    #[tokio::test]
+    async fn test_cache_hit() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("licenses.json"), LICENSES).unwrap();
+        let cache = cache(dir.path(), FixtureSource::default());
+        assert_eq!(cache.fetch(Resource::Licenses).await.unwrap(), LICENSES);
+    }
+
+    #[tokio::test]
+    async fn test_cache_stale_fallback() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("licenses.json"), LICENSES).unwrap();
+        let cache = cache(dir.path(), FixtureSource::default()).refresh(true);
+        assert_eq!(cache.fetch(Resource::Licenses).await.unwrap(), LICENSES);
+    }
+
+    #[tokio::test]
+    async fn test_cache_offline_miss() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = FixtureSource::default().with(Resource::Licenses, LICENSES);
+        let cache = cache(dir.path(), source).offline(true);
+        let text = cache.fetch(Resource::Licenses).await;
+        assert!(matches!(text, Err(Error::Offline(_))));
+    }
+
+    #[tokio::test]
+    async fn test_cache_details_keyed_by_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = FixtureSource::default()
+            .with(Resource::Licenses, LICENSES)
+            .with(Resource::LicenseDetails("MIT"), "{}");
+        let cache = cache(dir.path(), source);
+        cache.fetch(Resource::Licenses).await.unwrap();
+        cache.fetch(Resource::LicenseDetails("MIT")).await.unwrap();
+        assert!(dir.path().join("3.16/licenses.json").exists());
+        assert!(dir.path().join("3.16/MIT.json").exists());
+    }
+
+    #[tokio::test]
+    async fn test_cache_details_of_newer_source() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("licenses.json"), LICENSES).unwrap();
+        let newer = r#"{"licenseListVersion": "3.17"}"#;
+        let source = FixtureSource::default()
+            .with(Resource::Licenses, newer)
+            .with(Resource::Exceptions, newer)
+            .with(Resource::LicenseDetails("MIT"), "{}");
+        let cache = cache(dir.path(), source);
+        assert_eq!(cache.fetch(Resource::Licenses).await.unwrap(), LICENSES);
+        let text = cache.fetch(Resource::LicenseDetails("MIT")).await;
+        assert!(matches!(text, Err(Error::Outdated { .. })));
+        let text = cache.fetch(Resource::Exceptions).await;
+        assert!(matches!(text, Err(Error::Outdated { .. })));
+        assert!(!dir.path().join("3.16/MIT.json").exists());
+        assert!(!dir.path().join("3.16/exceptions.json").exists());
+        // The next run uses the newer list.
+        let licenses = std::fs::read_to_string(dir.path().join("licenses.json")).unwrap();
+        assert_eq!(licenses, newer);
+    }
+
+    #[tokio::test]
+    async fn test_cache_pinned() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = FixtureSource::default().with(Resource::Licenses, LICENSES);
+        let cache = cache(dir.path(), source).pin(Some("3.16".into()));
+        assert_eq!(cache.fetch(Resource::Licenses).await.unwrap(), LICENSES);
+        assert!(dir.path().join("3.16/licenses.json").exists());
+        assert!(!dir.path().join("licenses.json").exists());
+    }
+
+    #[tokio::test]
+    async fn test_cache_pinned_mismatch() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = FixtureSource::default().with(Resource::Licenses, LICENSES);
+        let cache = cache(dir.path(), source).pin(Some("3.21".into()));
+        let text = cache.fetch(Resource::Licenses).await;
+        assert!(matches!(text, Err(Error::VersionMismatch { .. })));
+        assert!(!dir.path().join("3.21/licenses.json").exists());
+    }
+}
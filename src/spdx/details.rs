@@ -1,3 +1,5 @@
+use super::template::{Copyright, Segment, Template, Var};
+use regex::Regex;
 use serde::Deserialize;
 
 #[derive(Deserialize, PartialEq, Debug)]
@@ -16,6 +18,78 @@ pub struct LicenseDetails {
     license_text_html: String,
 }
 
+impl LicenseDetails {
+    /// The license text with the placeholders of its copyright lines filled.
+    /// The rest of the text, including fixed copyright lines, is kept as is.
+    pub fn text_with_copyright(&self, copyright: &Copyright) -> String {
+        let mut text = self.license_text.clone();
+        if copyright.is_empty() {
+            return text;
+        }
+        let Ok(template) = Template::parse(&self.standard_license_template) else {
+            return text;
+        };
+        for var in copyright_vars(&template.segments) {
+            let Some(filled) = copyright.fill(&var.original) else {
+                continue;
+            };
+            // The text may be wrapped differently from the template.
+            let words: Vec<String> = var.original.split_whitespace().map(regex::escape).collect();
+            let Ok(original) = Regex::new(&words.join(r"\s+")) else {
+                continue;
+            };
+            if let Some(found) = original.find(&text) {
+                text.replace_range(found.range(), &filled);
+            }
+        }
+        text
+    }
+
+    /// The standard header to put in source files, for the licenses which
+    /// have one, with the copyright filled.
+    pub fn header_with_copyright(&self, copyright: &Copyright) -> Option<String> {
+        let template = self.standard_license_header_template.as_ref()?;
+        if template.trim().is_empty() {
+            return None;
+        }
+        render_with_copyright(template, copyright)
+    }
+
+    /// Whether the normalized `text` is an exact match of the license
+    /// template, see [`normalize`](super::matching::normalize).
+    pub fn matches(&self, text: &str) -> bool {
+        Template::parse(&self.standard_license_template)
+            .is_ok_and(|template| template.matches(text))
+    }
+
+    /// Whether the normalized `text` contains the license text.
+    pub fn is_contained_in(&self, text: &str) -> bool {
+        Template::parse(&self.standard_license_template)
+            .is_ok_and(|template| template.is_contained_in(text))
+    }
+}
+
+/// The `copyright` variables of a template, including the optional ones.
+fn copyright_vars(segments: &[Segment]) -> Vec<&Var> {
+    let mut vars = Vec::new();
+    for segment in segments {
+        match segment {
+            Segment::Var(var) if var.name == "copyright" => vars.push(var),
+            Segment::Optional(segments) => vars.extend(copyright_vars(segments)),
+            _ => {}
+        }
+    }
+    vars
+}
+
+fn render_with_copyright(template: &str, copyright: &Copyright) -> Option<String> {
+    let template = Template::parse(template).ok()?;
+    Some(template.render(&|var| match var.name.as_str() {
+        "copyright" => copyright.fill(&var.original),
+        _ => None,
+    }))
+}
+
 #[derive(Deserialize, PartialEq, Debug)]
 #[serde(rename_all = "camelCase")]
 struct CrossRef {
@@ -29,44 +103,63 @@ struct CrossRef {
     order: u32,
 }
 
-pub async fn get_details(url: String) -> Result<LicenseDetails, reqwest::Error> {
-    let resp = reqwest::get(url).await?.json::<LicenseDetails>().await?;
-    Ok(resp)
-}
-
 #[cfg(test)]
 mod tests {
+    use super::super::list::get_licenses;
+    use super::super::source::{FixtureSource, Resource};
     use super::*;
 
-    #[test]
-    fn test_license_details_mit() {
-        // https://spdx.org/licenses/MIT.json
-        let data = r#"
+    const MIT: &str = r#"
+    {
+        "isDeprecatedLicenseId": false,
+        "isFsfLibre": true,
+        "licenseText": "MIT License .. A very long text...",
+        "standardLicenseTemplate": "MIT License .. A very long text...",
+        "name": "MIT License",
+        "licenseId": "MIT",
+        "crossRef": [
         {
-            "isDeprecatedLicenseId": false,
-            "isFsfLibre": true,
-            "licenseText": "MIT License .. A very long text...",
-            "standardLicenseTemplate": "MIT License .. A very long text...",
-            "name": "MIT License",
-            "licenseId": "MIT",
-            "crossRef": [
+            "match": "false",
+            "url": "https://opensource.org/licenses/MIT",
+            "isValid": true,
+            "isLive": true,
+            "timestamp": "2022-02-06T22:01:14Z",
+            "isWayBackLink": false,
+            "order": 0
+        }
+        ],
+        "seeAlso": [
+            "https://opensource.org/licenses/MIT"
+        ],
+        "isOsiApproved": true,
+        "licenseTextHtml": "A very long text..."
+    }"#;
+
+    const LICENSES: &str = r#"
+    {
+        "licenseListVersion": "3.16",
+        "licenses": [
             {
-                "match": "false",
-                "url": "https://opensource.org/licenses/MIT",
-                "isValid": true,
-                "isLive": true,
-                "timestamp": "2022-02-06T22:01:14Z",
-                "isWayBackLink": false,
-                "order": 0
+                "reference": "https://spdx.org/licenses/MIT.html",
+                "isDeprecatedLicenseId": false,
+                "detailsUrl": "https://spdx.org/licenses/MIT.json",
+                "referenceNumber": 256,
+                "name": "MIT License",
+                "licenseId": "MIT",
+                "seeAlso": [
+                    "https://opensource.org/licenses/MIT"
+                ],
+                "isOsiApproved": true,
+                "isFsfLibre": true
             }
-            ],
-            "seeAlso": [
-                "https://opensource.org/licenses/MIT"
-            ],
-            "isOsiApproved": true,
-            "licenseTextHtml": "A very long text..."
-        }"#;
-        let lic_details: LicenseDetails = serde_json::from_str(data).unwrap();
+        ],
+        "releaseDate": "2022-02-06"
+    }"#;
+
+    #[test]
+    fn test_license_details_mit() {
+        // https://spdx.org/licenses/MIT.json
+        let lic_details: LicenseDetails = serde_json::from_str(MIT).unwrap();
         assert_eq!(
             lic_details,
             LicenseDetails {
@@ -121,9 +214,69 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_text_with_copyright() {
+        let mut lic_details: LicenseDetails = serde_json::from_str(MIT).unwrap();
+        // The `licenseText` layout differs from the template, which also has
+        // optional parts not in the text.
+        lic_details.license_text = String::from(
+            "MIT License\n\nCopyright (c) <year> <copyright\nholders>\n\nA very long\ntext...\n",
+        );
+        lic_details.standard_license_template = String::from(
+            "<<beginOptional>>MIT License<<endOptional>>\n\n<<var;name=\"copyright\";original=\"Copyright (c) <year> <copyright holders>\";match=\".{0,5000}\">>\n\nA very long text...<<beginOptional>> Optional<<endOptional>>",
+        );
+        assert_eq!(
+            lic_details.text_with_copyright(&Copyright::default()),
+            lic_details.license_text
+        );
+        let copyright = Copyright {
+            holder: Some(String::from("Nyakku Shigure")),
+            year: Some(String::from("2022")),
+        };
+        assert_eq!(
+            lic_details.text_with_copyright(&copyright),
+            "MIT License\n\nCopyright (c) 2022 Nyakku Shigure\n\nA very long\ntext...\n"
+        );
+
+        // A copyright without placeholders is not the user's to replace.
+        lic_details.license_text = String::from("Copyright (c) 2004 by ISC\n\nPermission");
+        lic_details.standard_license_template = String::from(
+            "<<var;name=\"copyright\";original=\"Copyright (c) 2004 by ISC\";match=\".{0,5000}\">>\n\nPermission",
+        );
+        assert_eq!(
+            lic_details.text_with_copyright(&copyright),
+            lic_details.license_text
+        );
+    }
+
+    #[test]
+    fn test_header_with_copyright() {
+        let mut lic_details: LicenseDetails = serde_json::from_str(MIT).unwrap();
+        assert_eq!(
+            lic_details.header_with_copyright(&Copyright::default()),
+            None
+        );
+        lic_details.standard_license_header_template = Some(String::from(
+            "Copyright <<var;name=\"copyright\";original=\"[yyyy] [name of copyright owner]\";match=\".+\">>\n\nLicensed under the Apache License",
+        ));
+        let copyright = Copyright {
+            holder: Some(String::from("Nyakku Shigure")),
+            year: None,
+        };
+        assert_eq!(
+            lic_details.header_with_copyright(&copyright).unwrap(),
+            "Copyright [yyyy] Nyakku Shigure\n\nLicensed under the Apache License"
+        );
+    }
+
     #[tokio::test]
     async fn test_get_details() {
-        let url = "https://spdx.org/licenses/MIT.json".into();
-        assert!(get_details(url).await.is_ok());
+        let source = FixtureSource::default()
+            .with(Resource::Licenses, LICENSES)
+            .with(Resource::LicenseDetails("MIT"), MIT);
+        let licenses = get_licenses(&source).await.unwrap();
+        let mit = licenses.get_license("MIT").unwrap();
+        let details = mit.get_details(&source).await.unwrap();
+        assert_eq!(details.license_text, "MIT License .. A very long text...");
     }
 }
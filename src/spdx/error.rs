@@ -0,0 +1,76 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum Error {
+    Network(reqwest::Error),
+    Io(PathBuf, io::Error),
+    Parse(serde_json::Error),
+    Missing(String),
+    Offline(String),
+    VersionMismatch {
+        expected: String,
+        found: String,
+    },
+    /// The unpinned source serves a newer list than the cached one in use
+    Outdated {
+        cached: String,
+        found: String,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Network(e) => match e.url() {
+                Some(url) => write!(
+                    f,
+                    "Cannot download {url}, check your network connection or use `--offline`."
+                ),
+                None => write!(f, "Cannot download the SPDX license data: {e}"),
+            },
+            Error::Io(path, e) => write!(f, "Cannot read {}: {e}.", path.display()),
+            Error::Parse(e) => write!(f, "Invalid SPDX license data: {e}."),
+            Error::Missing(path) => write!(f, "`{path}` is missing from the SPDX license data."),
+            Error::Offline(key) => write!(
+                f,
+                "`{key}` is not cached yet and network access is disabled by `--offline`."
+            ),
+            Error::VersionMismatch { expected, found } => write!(
+                f,
+                "Got version {found} of the SPDX license list while version {expected} is pinned."
+            ),
+            Error::Outdated { cached, found } => write!(
+                f,
+                "Version {found} of the SPDX license list replaced the cached {cached}, run the command again to use it."
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Network(e) => Some(e),
+            Error::Io(_, e) => Some(e),
+            Error::Parse(e) => Some(e),
+            Error::Missing(_)
+            | Error::Offline(_)
+            | Error::VersionMismatch { .. }
+            | Error::Outdated { .. } => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Self {
+        Error::Network(e)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::Parse(e)
+    }
+}
@@ -0,0 +1,173 @@
+use super::error::Error;
+use super::matching::normalize;
+use super::source::{LicenseSource, Resource};
+use super::template::Template;
+use serde::Deserialize;
+use strsim::jaro_winkler;
+
+#[derive(Deserialize, PartialEq, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Exceptions {
+    pub license_list_version: String,
+    pub exceptions: Vec<Exception>,
+    pub release_date: String,
+}
+
+#[derive(Deserialize, PartialEq, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Exception {
+    reference: String,
+    pub is_deprecated_license_id: bool,
+    details_url: String,
+    reference_number: u32,
+    pub name: String,
+    pub license_exception_id: String,
+    see_also: Vec<String>,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ExceptionDetails {
+    pub is_deprecated_license_id: bool,
+    pub license_exception_text: String,
+    standard_license_exception_template: Option<String>,
+    name: String,
+    license_exception_id: String,
+    see_also: Vec<String>,
+}
+
+pub async fn get_exceptions(source: &dyn LicenseSource) -> Result<Exceptions, Error> {
+    let text = source.fetch(Resource::Exceptions).await?;
+    Ok(serde_json::from_str(&text)?)
+}
+
+impl Exceptions {
+    pub fn similar_exceptions(&self, id: &str, num: usize) -> Vec<Exception> {
+        let mut exception_with_similarity: Vec<_> = self
+            .exceptions
+            .iter()
+            .map(|exception| {
+                (
+                    jaro_winkler(
+                        &exception.license_exception_id.to_lowercase(),
+                        &id.to_lowercase(),
+                    ),
+                    exception,
+                )
+            })
+            .collect();
+        exception_with_similarity.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
+        exception_with_similarity
+            .iter()
+            .take(num)
+            .map(|(_, exception)| (*exception).clone())
+            .collect()
+    }
+
+    pub fn get_exception_case_insensitive(&self, id: &str) -> Option<&Exception> {
+        self.exceptions
+            .iter()
+            .find(|&exception| exception.license_exception_id.to_lowercase() == id.to_lowercase())
+    }
+}
+
+impl ExceptionDetails {
+    /// Whether the normalized `text` contains the exception text, see
+    /// [`normalize`].
+    pub fn is_contained_in(&self, text: &str) -> bool {
+        match &self.standard_license_exception_template {
+            Some(template) => {
+                Template::parse(template).is_ok_and(|template| template.is_contained_in(text))
+            }
+            None => text.contains(&normalize(&self.license_exception_text)),
+        }
+    }
+}
+
+impl Exception {
+    pub async fn get_details(&self, source: &dyn LicenseSource) -> Result<ExceptionDetails, Error> {
+        let text = source
+            .fetch(Resource::ExceptionDetails(&self.license_exception_id))
+            .await?;
+        Ok(serde_json::from_str(&text)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::source::FixtureSource;
+    use super::*;
+
+    const EXCEPTIONS: &str = r#"
+    {
+        "licenseListVersion": "3.21",
+        "exceptions": [
+            {
+                "reference": "./LLVM-exception.json",
+                "isDeprecatedLicenseId": false,
+                "detailsUrl": "./LLVM-exception.html",
+                "referenceNumber": 12,
+                "name": "LLVM Exception",
+                "licenseExceptionId": "LLVM-exception",
+                "seeAlso": [
+                    "http://llvm.org/foundation/relicensing/LICENSE.txt"
+                ]
+            }
+        ],
+        "releaseDate": "2023-06-18"
+    }"#;
+
+    const LLVM_EXCEPTION: &str = r#"
+    {
+        "isDeprecatedLicenseId": false,
+        "licenseExceptionText": "---- LLVM Exceptions to the Apache 2.0 License ---- ...",
+        "standardLicenseExceptionTemplate": "---- LLVM Exceptions to the Apache 2.0 License ---- ...",
+        "name": "LLVM Exception",
+        "licenseComments": "LLVM Exceptions to the Apache 2.0 License",
+        "licenseExceptionId": "LLVM-exception",
+        "seeAlso": [
+            "http://llvm.org/foundation/relicensing/LICENSE.txt"
+        ],
+        "exceptionTextHtml": "..."
+    }"#;
+
+    #[test]
+    fn test_exceptions() {
+        let exceptions: Exceptions = serde_json::from_str(EXCEPTIONS).unwrap();
+        assert_eq!(
+            exceptions,
+            Exceptions {
+                license_list_version: String::from("3.21"),
+                exceptions: vec![Exception {
+                    reference: String::from("./LLVM-exception.json"),
+                    is_deprecated_license_id: false,
+                    details_url: String::from("./LLVM-exception.html"),
+                    reference_number: 12,
+                    name: String::from("LLVM Exception"),
+                    license_exception_id: String::from("LLVM-exception"),
+                    see_also: vec![String::from(
+                        "http://llvm.org/foundation/relicensing/LICENSE.txt"
+                    )],
+                }],
+                release_date: String::from("2023-06-18"),
+            }
+        );
+        assert!(exceptions
+            .get_exception_case_insensitive("llvm-exception")
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn test_get_exception_details() {
+        let source = FixtureSource::default()
+            .with(Resource::Exceptions, EXCEPTIONS)
+            .with(Resource::ExceptionDetails("LLVM-exception"), LLVM_EXCEPTION);
+        let exceptions = get_exceptions(&source).await.unwrap();
+        let llvm = &exceptions.similar_exceptions("llvm", 1)[0];
+        let details = llvm.get_details(&source).await.unwrap();
+        assert_eq!(
+            details.license_exception_text,
+            "---- LLVM Exceptions to the Apache 2.0 License ---- ..."
+        );
+    }
+}
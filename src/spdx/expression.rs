@@ -0,0 +1,862 @@
+//! SPDX license expressions, see
+//! https://spdx.github.io/spdx-spec/v2.3/SPDX-license-expressions/
+
+use super::exceptions::Exceptions;
+use super::list::Licenses;
+use std::fmt;
+use std::ops::Range;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expression {
+    License(LicenseItem),
+    And(Box<Expression>, Box<Expression>),
+    Or(Box<Expression>, Box<Expression>),
+}
+
+/// A simple expression, optionally followed by `WITH <exception>`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LicenseItem {
+    pub license: LicenseId,
+    /// Trailing `+`
+    pub or_later: bool,
+    pub exception: Option<String>,
+    /// Byte range of the license id in the parsed text
+    pub span: Range<usize>,
+    /// Byte range of the exception id in the parsed text
+    pub exception_span: Option<Range<usize>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LicenseId {
+    Spdx(String),
+    /// `[DocumentRef-<document>:]LicenseRef-<license>`
+    Ref {
+        document: Option<String>,
+        license: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseErrorKind {
+    Empty,
+    UnexpectedEnd,
+    UnexpectedToken(String),
+    InvalidCharacter(char),
+    InvalidLicenseRef(String),
+    /// `and`, `Or`, ..., operators must be uppercase
+    LowercaseOperator(String),
+    UnclosedParenthesis,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    /// Byte offset in the parsed text
+    pub position: usize,
+    pub kind: ParseErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationErrorKind {
+    UnknownLicense(String),
+    UnknownException(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationError {
+    pub span: Range<usize>,
+    pub kind: ValidationErrorKind,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ParseErrorKind::Empty => write!(f, "empty license expression"),
+            ParseErrorKind::UnexpectedEnd => write!(f, "unexpected end of license expression"),
+            ParseErrorKind::UnexpectedToken(token) => {
+                write!(f, "unexpected `{token}` at position {}", self.position)
+            }
+            ParseErrorKind::InvalidCharacter(c) => {
+                write!(f, "invalid character `{c}` at position {}", self.position)
+            }
+            ParseErrorKind::InvalidLicenseRef(id) => {
+                write!(
+                    f,
+                    "invalid license reference `{id}` at position {}",
+                    self.position
+                )
+            }
+            ParseErrorKind::LowercaseOperator(operator) => {
+                write!(
+                    f,
+                    "operator `{operator}` at position {} must be uppercase, use `{}`",
+                    self.position,
+                    operator.to_uppercase()
+                )
+            }
+            ParseErrorKind::UnclosedParenthesis => {
+                write!(f, "unclosed parenthesis at position {}", self.position)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ValidationErrorKind::UnknownLicense(id) => {
+                write!(
+                    f,
+                    "unknown license id `{id}` at position {}",
+                    self.span.start
+                )
+            }
+            ValidationErrorKind::UnknownException(id) => {
+                write!(
+                    f,
+                    "unknown license exception id `{id}` at position {}",
+                    self.span.start
+                )
+            }
+        }
+    }
+}
+
+/// Points at `span` of `text` with carets, for error messages.
+pub fn underline(text: &str, span: Range<usize>) -> String {
+    // Errors at the end of `text` point just past it.
+    let span = span.start.min(text.len())..span.end.min(text.len());
+    let start = text[..span.start].chars().count();
+    let width = text[span].chars().count().max(1);
+    format!("{text}\n{}{}", " ".repeat(start), "^".repeat(width))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token<'a> {
+    Word(&'a str),
+    Plus,
+    Open,
+    Close,
+    And,
+    Or,
+    With,
+}
+
+impl fmt::Display for Token<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Word(word) => write!(f, "{word}"),
+            Token::Plus => write!(f, "+"),
+            Token::Open => write!(f, "("),
+            Token::Close => write!(f, ")"),
+            Token::And => write!(f, "AND"),
+            Token::Or => write!(f, "OR"),
+            Token::With => write!(f, "WITH"),
+        }
+    }
+}
+
+fn is_id_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '-' || c == '.' || c == ':'
+}
+
+fn tokenize(text: &str) -> Result<Vec<(usize, Token<'_>)>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = text.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' | ')' | '+' => {
+                chars.next();
+                let token = match c {
+                    '(' => Token::Open,
+                    ')' => Token::Close,
+                    _ => Token::Plus,
+                };
+                tokens.push((start, token));
+            }
+            c if is_id_char(c) => {
+                let mut end = start;
+                while let Some(&(i, c)) = chars.peek() {
+                    if !is_id_char(c) {
+                        break;
+                    }
+                    end = i + c.len_utf8();
+                    chars.next();
+                }
+                // Operators are case-sensitive, as in SPDX 2.3 Annex D.
+                let token = match &text[start..end] {
+                    "AND" => Token::And,
+                    "OR" => Token::Or,
+                    "WITH" => Token::With,
+                    word if ["AND", "OR", "WITH"]
+                        .iter()
+                        .any(|operator| operator.eq_ignore_ascii_case(word)) =>
+                    {
+                        return Err(ParseError {
+                            position: start,
+                            kind: ParseErrorKind::LowercaseOperator(word.into()),
+                        })
+                    }
+                    word => Token::Word(word),
+                };
+                tokens.push((start, token));
+            }
+            c => {
+                return Err(ParseError {
+                    position: start,
+                    kind: ParseErrorKind::InvalidCharacter(c),
+                })
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    tokens: Vec<(usize, Token<'a>)>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&Token<'a>> {
+        self.tokens.get(self.pos).map(|(_, token)| token)
+    }
+
+    fn next(&mut self) -> Option<(usize, Token<'a>)> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn unexpected(&self, position: usize) -> ParseError {
+        let token = self
+            .tokens
+            .iter()
+            .find(|(start, _)| *start == position)
+            .map(|(_, token)| token.to_string())
+            .unwrap_or_default();
+        ParseError {
+            position,
+            kind: ParseErrorKind::UnexpectedToken(token),
+        }
+    }
+
+    fn unexpected_end(&self) -> ParseError {
+        ParseError {
+            position: self.text.len(),
+            kind: ParseErrorKind::UnexpectedEnd,
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Expression, ParseError> {
+        let mut left = self.parse_and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.next();
+            let right = self.parse_and()?;
+            left = Expression::Or(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Expression, ParseError> {
+        let mut left = self.parse_primary()?;
+        while self.peek() == Some(&Token::And) {
+            self.next();
+            let right = self.parse_primary()?;
+            left = Expression::And(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_primary(&mut self) -> Result<Expression, ParseError> {
+        match self.next() {
+            Some((start, Token::Open)) => {
+                let expression = self.parse_or()?;
+                match self.next() {
+                    Some((_, Token::Close)) => Ok(expression),
+                    Some((position, _)) => Err(self.unexpected(position)),
+                    None => Err(ParseError {
+                        position: start,
+                        kind: ParseErrorKind::UnclosedParenthesis,
+                    }),
+                }
+            }
+            Some((start, Token::Word(word))) => self.parse_license(start, word),
+            Some((position, _)) => Err(self.unexpected(position)),
+            None => Err(self.unexpected_end()),
+        }
+    }
+
+    fn parse_license(&mut self, start: usize, word: &str) -> Result<Expression, ParseError> {
+        let span = start..start + word.len();
+        let license = parse_license_id(word).ok_or(ParseError {
+            position: start,
+            kind: ParseErrorKind::InvalidLicenseRef(word.into()),
+        })?;
+        let or_later = self.peek() == Some(&Token::Plus);
+        if or_later {
+            let (position, _) = self.next().unwrap();
+            if matches!(license, LicenseId::Ref { .. }) {
+                return Err(self.unexpected(position));
+            }
+        }
+        let (exception, exception_span) = if self.peek() == Some(&Token::With) {
+            self.next();
+            match self.next() {
+                Some((start, Token::Word(word))) if !word.contains(':') => {
+                    (Some(word.to_string()), Some(start..start + word.len()))
+                }
+                Some((position, _)) => return Err(self.unexpected(position)),
+                None => return Err(self.unexpected_end()),
+            }
+        } else {
+            (None, None)
+        };
+        Ok(Expression::License(LicenseItem {
+            license,
+            or_later,
+            exception,
+            span,
+            exception_span,
+        }))
+    }
+}
+
+fn parse_license_id(word: &str) -> Option<LicenseId> {
+    let (document, license) = match word.split_once(':') {
+        Some((document, license)) => {
+            let document = document.strip_prefix("DocumentRef-")?;
+            (Some(document), license)
+        }
+        None => (None, word),
+    };
+    match license.strip_prefix("LicenseRef-") {
+        Some(license) if !license.is_empty() && !license.contains(':') => {
+            if document.is_some_and(str::is_empty) {
+                return None;
+            }
+            Some(LicenseId::Ref {
+                document: document.map(String::from),
+                license: license.into(),
+            })
+        }
+        Some(_) => None,
+        None if document.is_none() => Some(LicenseId::Spdx(word.into())),
+        None => None,
+    }
+}
+
+impl Expression {
+    pub fn parse(text: &str) -> Result<Expression, ParseError> {
+        let tokens = tokenize(text)?;
+        if tokens.is_empty() {
+            return Err(ParseError {
+                position: 0,
+                kind: ParseErrorKind::Empty,
+            });
+        }
+        let mut parser = Parser {
+            text,
+            tokens,
+            pos: 0,
+        };
+        let expression = parser.parse_or()?;
+        match parser.next() {
+            None => Ok(expression),
+            Some((position, _)) => Err(parser.unexpected(position)),
+        }
+    }
+
+    /// Every license in the expression, from left to right.
+    pub fn licenses(&self) -> Vec<&LicenseItem> {
+        match self {
+            Expression::License(item) => vec![item],
+            Expression::And(left, right) | Expression::Or(left, right) => {
+                let mut items = left.licenses();
+                items.extend(right.licenses());
+                items
+            }
+        }
+    }
+
+    /// Distinct SPDX license ids in the expression, from left to right.
+    pub fn license_ids(&self) -> Vec<&str> {
+        let mut ids: Vec<&str> = Vec::new();
+        for item in self.licenses() {
+            if let LicenseId::Spdx(id) = &item.license {
+                if !ids.contains(&id.as_str()) {
+                    ids.push(id);
+                }
+            }
+        }
+        ids
+    }
+
+    fn licenses_mut(&mut self) -> Vec<&mut LicenseItem> {
+        match self {
+            Expression::License(item) => vec![item],
+            Expression::And(left, right) | Expression::Or(left, right) => {
+                let mut items = left.licenses_mut();
+                items.extend(right.licenses_mut());
+                items
+            }
+        }
+    }
+
+    pub fn has_exceptions(&self) -> bool {
+        self.licenses().iter().any(|item| item.exception.is_some())
+    }
+
+    /// Checks every SPDX license and exception id against `licenses` and
+    /// `exceptions`, returning the expression with the ids in their canonical case.
+    pub fn validate(
+        &self,
+        licenses: &Licenses,
+        exceptions: &Exceptions,
+    ) -> Result<Expression, Vec<ValidationError>> {
+        let mut expression = self.clone();
+        let mut errors = Vec::new();
+        for item in expression.licenses_mut() {
+            if let LicenseId::Spdx(id) = &mut item.license {
+                match licenses.get_license_case_insensitive(id) {
+                    Some(license) => *id = license.license_id.clone(),
+                    None => errors.push(ValidationError {
+                        span: item.span.clone(),
+                        kind: ValidationErrorKind::UnknownLicense(id.clone()),
+                    }),
+                }
+            }
+            if let (Some(id), Some(span)) = (&mut item.exception, &item.exception_span) {
+                match exceptions.get_exception_case_insensitive(id) {
+                    Some(exception) => *id = exception.license_exception_id.clone(),
+                    None => errors.push(ValidationError {
+                        span: span.clone(),
+                        kind: ValidationErrorKind::UnknownException(id.clone()),
+                    }),
+                }
+            }
+        }
+        if errors.is_empty() {
+            Ok(expression)
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Replaces the deprecated license ids by their current replacements,
+    /// see [`Licenses::replacement`], or `None` if there are none.
+    pub fn replace_deprecated(&self, licenses: &Licenses) -> Option<Expression> {
+        let mut replaced = false;
+        let expression = self.clone().map_licenses(&mut |item| {
+            let LicenseId::Spdx(id) = &item.license else {
+                return Expression::License(item);
+            };
+            let id = if item.or_later {
+                format!("{id}+")
+            } else {
+                id.clone()
+            };
+            let Some(replacement) = licenses.replacement(&id) else {
+                return Expression::License(item);
+            };
+            // The replacements are simple expressions, `WITH` at most.
+            let Ok(Expression::License(mut replacement)) = Expression::parse(&replacement) else {
+                return Expression::License(item);
+            };
+            // Only one exception can be kept, which is the user's call.
+            if replacement.exception.is_some() && item.exception.is_some() {
+                return Expression::License(item);
+            }
+            replaced = true;
+            replacement.span = item.span;
+            if replacement.exception.is_none() {
+                replacement.exception = item.exception;
+                replacement.exception_span = item.exception_span;
+            } else {
+                replacement.exception_span = None;
+            }
+            Expression::License(replacement)
+        });
+        replaced.then_some(expression)
+    }
+
+    /// Whether both expressions grant the same licenses: SPDX ids are matched
+    /// case-insensitively, and the order of the operands of `AND` and `OR`
+    /// does not matter.
+    pub fn is_equivalent(&self, other: &Expression) -> bool {
+        self.canonical_form() == other.canonical_form()
+    }
+
+    fn canonical_form(&self) -> String {
+        let (operator, operands) = match self {
+            Expression::License(item) => {
+                let license = match &item.license {
+                    LicenseId::Spdx(id) => id.to_lowercase(),
+                    license => license.to_string(),
+                };
+                let plus = if item.or_later { "+" } else { "" };
+                return match &item.exception {
+                    Some(exception) => format!("{license}{plus} with {}", exception.to_lowercase()),
+                    None => format!("{license}{plus}"),
+                };
+            }
+            Expression::And(..) => ("AND", self.operands()),
+            Expression::Or(..) => ("OR", self.operands()),
+        };
+        let mut operands: Vec<String> = operands.iter().map(|e| e.canonical_form()).collect();
+        operands.sort();
+        operands.dedup();
+        format!("({})", operands.join(&format!(" {operator} ")))
+    }
+
+    /// The operands of a chain of the same operator, e.g. `a`, `b` and `c`
+    /// of `a AND (b AND c)`.
+    fn operands(&self) -> Vec<&Expression> {
+        let (Expression::And(left, right) | Expression::Or(left, right)) = self else {
+            return vec![self];
+        };
+        let mut operands = Vec::new();
+        for operand in [left.as_ref(), right.as_ref()] {
+            match (self, operand) {
+                (Expression::And(..), Expression::And(..))
+                | (Expression::Or(..), Expression::Or(..)) => operands.extend(operand.operands()),
+                _ => operands.push(operand),
+            }
+        }
+        operands
+    }
+
+    fn map_licenses(self, f: &mut impl FnMut(LicenseItem) -> Expression) -> Expression {
+        match self {
+            Expression::License(item) => f(item),
+            Expression::And(left, right) => Expression::And(
+                Box::new(left.map_licenses(f)),
+                Box::new(right.map_licenses(f)),
+            ),
+            Expression::Or(left, right) => Expression::Or(
+                Box::new(left.map_licenses(f)),
+                Box::new(right.map_licenses(f)),
+            ),
+        }
+    }
+}
+
+impl fmt::Display for LicenseId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LicenseId::Spdx(id) => write!(f, "{id}"),
+            LicenseId::Ref {
+                document: Some(document),
+                license,
+            } => write!(f, "DocumentRef-{document}:LicenseRef-{license}"),
+            LicenseId::Ref {
+                document: None,
+                license,
+            } => write!(f, "LicenseRef-{license}"),
+        }
+    }
+}
+
+impl fmt::Display for LicenseItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.license)?;
+        if self.or_later {
+            write!(f, "+")?;
+        }
+        if let Some(exception) = &self.exception {
+            write!(f, " WITH {exception}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expression::License(item) => write!(f, "{item}"),
+            Expression::Or(left, right) => write!(f, "{left} OR {right}"),
+            Expression::And(left, right) => {
+                for (i, operand) in [left, right].into_iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " AND ")?;
+                    }
+                    match operand.as_ref() {
+                        Expression::Or(..) => write!(f, "({operand})")?,
+                        _ => write!(f, "{operand}")?,
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn license(id: &str, span: Range<usize>) -> Expression {
+        Expression::License(LicenseItem {
+            license: LicenseId::Spdx(id.into()),
+            or_later: false,
+            exception: None,
+            span,
+            exception_span: None,
+        })
+    }
+
+    #[test]
+    fn test_parse_simple() {
+        assert_eq!(Expression::parse("MIT"), Ok(license("MIT", 0..3)));
+        assert_eq!(Expression::parse("  MIT "), Ok(license("MIT", 2..5)));
+    }
+
+    #[test]
+    fn test_parse_precedence() {
+        let expression = Expression::parse("MIT OR Apache-2.0 AND BSD-3-Clause").unwrap();
+        assert_eq!(
+            expression,
+            Expression::Or(
+                Box::new(license("MIT", 0..3)),
+                Box::new(Expression::And(
+                    Box::new(license("Apache-2.0", 7..17)),
+                    Box::new(license("BSD-3-Clause", 22..34)),
+                )),
+            )
+        );
+        let expression = Expression::parse("(MIT OR Apache-2.0) AND BSD-3-Clause").unwrap();
+        assert!(matches!(expression, Expression::And(..)));
+        assert_eq!(
+            expression.to_string(),
+            "(MIT OR Apache-2.0) AND BSD-3-Clause"
+        );
+    }
+
+    #[test]
+    fn test_parse_with_and_plus() {
+        let expression = Expression::parse("GPL-2.0+ WITH Classpath-exception-2.0 OR mit").unwrap();
+        let items = expression.licenses();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].license, LicenseId::Spdx("GPL-2.0".into()));
+        assert!(items[0].or_later);
+        assert_eq!(items[0].exception, Some("Classpath-exception-2.0".into()));
+        assert_eq!(items[0].exception_span, Some(14..37));
+        assert_eq!(
+            expression.to_string(),
+            "GPL-2.0+ WITH Classpath-exception-2.0 OR mit"
+        );
+    }
+
+    #[test]
+    fn test_is_equivalent() {
+        let equivalent = |a, b| {
+            Expression::parse(a)
+                .unwrap()
+                .is_equivalent(&Expression::parse(b).unwrap())
+        };
+        assert!(equivalent("MIT OR Apache-2.0", "Apache-2.0 OR MIT"));
+        assert!(equivalent("mit", "MIT"));
+        assert!(equivalent(
+            "(MIT OR Apache-2.0) AND (BSD-3-Clause AND ISC)",
+            "ISC AND (Apache-2.0 OR MIT) AND BSD-3-Clause"
+        ));
+        assert!(!equivalent("MIT OR Apache-2.0", "MIT AND Apache-2.0"));
+        assert!(!equivalent(
+            "MIT AND (ISC OR Zlib)",
+            "(MIT AND ISC) OR Zlib"
+        ));
+        assert!(!equivalent("GPL-2.0+", "GPL-2.0"));
+    }
+
+    #[test]
+    fn test_parse_license_ref() {
+        let expression = Expression::parse(
+            "LicenseRef-Foo AND DocumentRef-spdx-tool-1.2:LicenseRef-MIT-Style-2",
+        )
+        .unwrap();
+        let items = expression.licenses();
+        assert_eq!(
+            items[0].license,
+            LicenseId::Ref {
+                document: None,
+                license: "Foo".into()
+            }
+        );
+        assert_eq!(
+            items[1].license,
+            LicenseId::Ref {
+                document: Some("spdx-tool-1.2".into()),
+                license: "MIT-Style-2".into()
+            }
+        );
+        assert!(expression.license_ids().is_empty());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |text| Expression::parse(text).unwrap_err();
+        assert_eq!(error("").kind, ParseErrorKind::Empty);
+        assert_eq!(
+            error("MIT OR"),
+            ParseError {
+                position: 6,
+                kind: ParseErrorKind::UnexpectedEnd
+            }
+        );
+        assert_eq!(
+            error("MIT Apache-2.0"),
+            ParseError {
+                position: 4,
+                kind: ParseErrorKind::UnexpectedToken("Apache-2.0".into())
+            }
+        );
+        assert_eq!(
+            error("(MIT OR Apache-2.0"),
+            ParseError {
+                position: 0,
+                kind: ParseErrorKind::UnclosedParenthesis
+            }
+        );
+        assert_eq!(
+            error("MIT, Apache-2.0"),
+            ParseError {
+                position: 3,
+                kind: ParseErrorKind::InvalidCharacter(',')
+            }
+        );
+        assert_eq!(
+            error("MIT AND Foo:Bar"),
+            ParseError {
+                position: 8,
+                kind: ParseErrorKind::InvalidLicenseRef("Foo:Bar".into())
+            }
+        );
+        assert_eq!(error("LicenseRef-Foo+").position, 14);
+        assert_eq!(
+            error("MIT AND (OR Apache-2.0)").kind,
+            ParseErrorKind::UnexpectedToken("OR".into())
+        );
+        assert_eq!(error("MIT WITH").kind, ParseErrorKind::UnexpectedEnd);
+        assert_eq!(
+            error("MIT or Apache-2.0"),
+            ParseError {
+                position: 4,
+                kind: ParseErrorKind::LowercaseOperator("or".into())
+            }
+        );
+        assert_eq!(
+            error("MIT With LLVM-exception").to_string(),
+            "operator `With` at position 4 must be uppercase, use `WITH`"
+        );
+    }
+
+    #[test]
+    fn test_license_ids() {
+        let expression = Expression::parse("MIT OR (Apache-2.0 AND MIT)").unwrap();
+        assert_eq!(expression.license_ids(), vec!["MIT", "Apache-2.0"]);
+    }
+
+    #[test]
+    fn test_validate() {
+        let licenses: Licenses = serde_json::from_str(
+            r#"
+            {
+                "licenseListVersion": "3.16",
+                "licenses": [
+                    {
+                        "reference": "https://spdx.org/licenses/MIT.html",
+                        "isDeprecatedLicenseId": false,
+                        "detailsUrl": "https://spdx.org/licenses/MIT.json",
+                        "referenceNumber": 256,
+                        "name": "MIT License",
+                        "licenseId": "MIT",
+                        "seeAlso": [],
+                        "isOsiApproved": true
+                    }
+                ],
+                "releaseDate": "2022-02-06"
+            }"#,
+        )
+        .unwrap();
+        let exceptions: Exceptions = serde_json::from_str(
+            r#"
+            {
+                "licenseListVersion": "3.16",
+                "exceptions": [
+                    {
+                        "reference": "./LLVM-exception.json",
+                        "isDeprecatedLicenseId": false,
+                        "detailsUrl": "./LLVM-exception.html",
+                        "referenceNumber": 12,
+                        "name": "LLVM Exception",
+                        "licenseExceptionId": "LLVM-exception",
+                        "seeAlso": []
+                    }
+                ],
+                "releaseDate": "2022-02-06"
+            }"#,
+        )
+        .unwrap();
+        let expression = Expression::parse("mit WITH llvm-exception OR LicenseRef-Foo").unwrap();
+        assert_eq!(
+            expression
+                .validate(&licenses, &exceptions)
+                .unwrap()
+                .to_string(),
+            "MIT WITH LLVM-exception OR LicenseRef-Foo"
+        );
+        let expression = Expression::parse("MIT AND Foo WITH Bar").unwrap();
+        assert_eq!(
+            expression.validate(&licenses, &exceptions),
+            Err(vec![
+                ValidationError {
+                    span: 8..11,
+                    kind: ValidationErrorKind::UnknownLicense("Foo".into())
+                },
+                ValidationError {
+                    span: 17..20,
+                    kind: ValidationErrorKind::UnknownException("Bar".into())
+                }
+            ])
+        );
+    }
+
+    #[test]
+    fn test_replace_deprecated() {
+        let licenses = Licenses {
+            license_list_version: "3.16".into(),
+            licenses: Vec::new(),
+            release_date: "2022-02-06".into(),
+        };
+        let replace = |text| {
+            Expression::parse(text)
+                .unwrap()
+                .replace_deprecated(&licenses)
+                .map(|expression| expression.to_string())
+        };
+        assert_eq!(
+            replace("MIT AND (GPL-2.0+ OR LGPL-2.1 WITH LLVM-exception)"),
+            Some("MIT AND (GPL-2.0-or-later OR LGPL-2.1-only WITH LLVM-exception)".into())
+        );
+        assert_eq!(
+            replace("wxWindows"),
+            Some("GPL-2.0-or-later WITH WxWindows-exception-3.1".into())
+        );
+        assert_eq!(
+            replace("MIT OR GPL-2.0-with-classpath-exception WITH LLVM-exception"),
+            None
+        );
+        assert_eq!(replace("MIT OR Apache-2.0"), None);
+    }
+
+    #[test]
+    fn test_underline() {
+        assert_eq!(underline("MIT OR Foo", 7..10), "MIT OR Foo\n       ^^^");
+        assert_eq!(underline("MIT OR", 6..7), "MIT OR\n      ^");
+    }
+}
@@ -1,4 +1,6 @@
-use super::details::{get_details, LicenseDetails};
+use super::details::LicenseDetails;
+use super::error::Error;
+use super::source::{LicenseSource, Resource};
 use serde::Deserialize;
 use strsim::jaro_winkler;
 
@@ -24,10 +26,60 @@ pub struct License {
     is_fsf_libre: Option<bool>,
 }
 
-pub async fn get_licenses() -> Result<Licenses, reqwest::Error> {
-    let url = "https://spdx.org/licenses/licenses.json".to_string();
-    let resp = reqwest::get(url).await?.json::<Licenses>().await?;
-    Ok(resp)
+/// Deprecated license ids and the expressions replacing them. The `or later`
+/// forms (e.g. `GPL-2.0+`) are derived from these.
+const REPLACEMENTS: &[(&str, &str)] = &[
+    ("AGPL-1.0", "AGPL-1.0-only"),
+    ("AGPL-3.0", "AGPL-3.0-only"),
+    ("BSD-2-Clause-FreeBSD", "BSD-2-Clause"),
+    ("BSD-2-Clause-NetBSD", "BSD-2-Clause"),
+    ("bzip2-1.0.5", "bzip2-1.0.6"),
+    ("eCos-2.0", "GPL-2.0-or-later WITH eCos-exception-2.0"),
+    ("GFDL-1.1", "GFDL-1.1-only"),
+    ("GFDL-1.2", "GFDL-1.2-only"),
+    ("GFDL-1.3", "GFDL-1.3-only"),
+    ("GPL-1.0", "GPL-1.0-only"),
+    ("GPL-2.0", "GPL-2.0-only"),
+    (
+        "GPL-2.0-with-autoconf-exception",
+        "GPL-2.0-only WITH Autoconf-exception-2.0",
+    ),
+    (
+        "GPL-2.0-with-bison-exception",
+        "GPL-2.0-or-later WITH Bison-exception-2.2",
+    ),
+    (
+        "GPL-2.0-with-classpath-exception",
+        "GPL-2.0-only WITH Classpath-exception-2.0",
+    ),
+    (
+        "GPL-2.0-with-font-exception",
+        "GPL-2.0-only WITH Font-exception-2.0",
+    ),
+    (
+        "GPL-2.0-with-GCC-exception",
+        "GPL-2.0-only WITH GCC-exception-2.0",
+    ),
+    ("GPL-3.0", "GPL-3.0-only"),
+    (
+        "GPL-3.0-with-autoconf-exception",
+        "GPL-3.0-only WITH Autoconf-exception-3.0",
+    ),
+    (
+        "GPL-3.0-with-GCC-exception",
+        "GPL-3.0-only WITH GCC-exception-3.1",
+    ),
+    ("LGPL-2.0", "LGPL-2.0-only"),
+    ("LGPL-2.1", "LGPL-2.1-only"),
+    ("LGPL-3.0", "LGPL-3.0-only"),
+    ("Nunit", "zlib-acknowledgement"),
+    ("StandardML-NJ", "SMLNJ"),
+    ("wxWindows", "GPL-2.0-or-later WITH WxWindows-exception-3.1"),
+];
+
+pub async fn get_licenses(source: &dyn LicenseSource) -> Result<Licenses, Error> {
+    let text = source.fetch(Resource::Licenses).await?;
+    Ok(serde_json::from_str(&text)?)
 }
 
 impl Licenses {
@@ -62,8 +114,9 @@ impl Licenses {
             })
             .collect();
         license_with_similarity.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
-        license_with_similarity[0..num]
+        license_with_similarity
             .iter()
+            .take(num)
             .map(|(_, license)| license.to_owned().clone())
             .collect()
     }
@@ -82,16 +135,73 @@ impl Licenses {
     }
 }
 
+impl Licenses {
+    /// The expression replacing a deprecated license id, `+` included, e.g.
+    /// `GPL-3.0-only` for `GPL-3.0` and `GPL-2.0-or-later` for `GPL-2.0+`.
+    pub fn replacement(&self, id: &str) -> Option<String> {
+        let (id, or_later) = match id.strip_suffix('+') {
+            Some(id) => (id, true),
+            None => (id, false),
+        };
+        if self
+            .get_license_case_insensitive(id)
+            .is_some_and(|license| !license.is_deprecated_license_id)
+        {
+            return None;
+        }
+        let (_, replacement) = REPLACEMENTS
+            .iter()
+            .find(|(deprecated, _)| deprecated.eq_ignore_ascii_case(id))?;
+        if !or_later {
+            return Some(replacement.to_string());
+        }
+        let (license, exception) = match replacement.split_once(' ') {
+            Some((license, exception)) => (license, format!(" {exception}")),
+            None => (*replacement, String::new()),
+        };
+        let license = match license.strip_suffix("-only") {
+            Some(license) => format!("{license}-or-later"),
+            None if license.ends_with("-or-later") => license.to_string(),
+            None => format!("{license}+"),
+        };
+        Some(license + &exception)
+    }
+}
+
 impl License {
-    pub async fn get_details(&self) -> Result<LicenseDetails, reqwest::Error> {
-        get_details(self.details_url.clone()).await
+    pub async fn get_details(&self, source: &dyn LicenseSource) -> Result<LicenseDetails, Error> {
+        let text = source
+            .fetch(Resource::LicenseDetails(&self.license_id))
+            .await?;
+        Ok(serde_json::from_str(&text)?)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::super::source::FixtureSource;
     use super::*;
 
+    const LICENSES: &str = r#"
+        {
+            "licenseListVersion": "3.16",
+            "licenses": [
+                {
+                    "reference": "https://spdx.org/licenses/CC-BY-NC-SA-4.0.html",
+                    "isDeprecatedLicenseId": false,
+                    "detailsUrl": "https://spdx.org/licenses/CC-BY-NC-SA-4.0.json",
+                    "referenceNumber": 97,
+                    "name": "Creative Commons Attribution Non Commercial Share Alike 4.0 International",
+                    "licenseId": "CC-BY-NC-SA-4.0",
+                    "seeAlso": [
+                        "https://creativecommons.org/licenses/by-nc-sa/4.0/legalcode"
+                    ],
+                    "isOsiApproved": false
+                }
+            ],
+            "releaseDate": "2022-02-06"
+        }"#;
+
     #[test]
     fn test_license_lgpl_3_0() {
         let data = r#"
@@ -167,26 +277,7 @@ mod tests {
 
     #[test]
     fn test_licenses() {
-        let data = r#"
-        {
-            "licenseListVersion": "3.16",
-            "licenses": [
-                {
-                    "reference": "https://spdx.org/licenses/CC-BY-NC-SA-4.0.html",
-                    "isDeprecatedLicenseId": false,
-                    "detailsUrl": "https://spdx.org/licenses/CC-BY-NC-SA-4.0.json",
-                    "referenceNumber": 97,
-                    "name": "Creative Commons Attribution Non Commercial Share Alike 4.0 International",
-                    "licenseId": "CC-BY-NC-SA-4.0",
-                    "seeAlso": [
-                        "https://creativecommons.org/licenses/by-nc-sa/4.0/legalcode"
-                    ],
-                    "isOsiApproved": false
-                }
-            ],
-            "releaseDate": "2022-02-06"
-        }"#;
-        let lic: Licenses = serde_json::from_str(data).unwrap();
+        let lic: Licenses = serde_json::from_str(LICENSES).unwrap();
         assert_eq!(
             lic,
             Licenses {
@@ -213,6 +304,24 @@ mod tests {
 
     #[tokio::test]
     async fn test_get_licenses() {
-        assert!(get_licenses().await.is_ok());
+        let source = FixtureSource::default().with(Resource::Licenses, LICENSES);
+        let licenses = get_licenses(&source).await.unwrap();
+        assert_eq!(licenses.license_list_version, "3.16");
+        assert!(licenses.contains("CC-BY-NC-SA-4.0"));
+    }
+
+    #[test]
+    fn test_replacement() {
+        let licenses: Licenses = serde_json::from_str(LICENSES).unwrap();
+        let replacement = |id| licenses.replacement(id);
+        assert_eq!(replacement("GPL-3.0"), Some("GPL-3.0-only".into()));
+        assert_eq!(replacement("gpl-2.0+"), Some("GPL-2.0-or-later".into()));
+        assert_eq!(replacement("StandardML-NJ+"), Some("SMLNJ+".into()));
+        assert_eq!(
+            replacement("GPL-2.0-with-classpath-exception+"),
+            Some("GPL-2.0-or-later WITH Classpath-exception-2.0".into())
+        );
+        assert_eq!(replacement("CC-BY-NC-SA-4.0"), None);
+        assert_eq!(replacement("MIT"), None);
     }
 }
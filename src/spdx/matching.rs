@@ -0,0 +1,236 @@
+//! Matching license texts against SPDX license templates, following
+//! https://spdx.github.io/spdx-spec/v2.3/license-matching-guidelines-and-templates/
+
+use super::template::{Segment, Template};
+use regex::{Regex, RegexBuilder};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Spellings considered the same by the matching guidelines (B.3.5), as
+/// (variant, spelling kept).
+const EQUIVALENT_WORDS: &[(&str, &str)] = &[
+    ("acknowledgement", "acknowledgment"),
+    ("analogue", "analog"),
+    ("analyse", "analyze"),
+    ("artefact", "artifact"),
+    ("authorisation", "authorization"),
+    ("authorised", "authorized"),
+    ("calibre", "caliber"),
+    ("cancelled", "canceled"),
+    ("capitalisations", "capitalizations"),
+    ("catalogue", "catalog"),
+    ("categorise", "categorize"),
+    ("centre", "center"),
+    ("emphasised", "emphasized"),
+    ("favour", "favor"),
+    ("favourite", "favorite"),
+    ("fulfil", "fulfill"),
+    ("fulfilment", "fulfillment"),
+    ("initialise", "initialize"),
+    ("judgement", "judgment"),
+    ("labelling", "labeling"),
+    ("labour", "labor"),
+    ("licence", "license"),
+    ("maximise", "maximize"),
+    ("modelled", "modeled"),
+    ("modelling", "modeling"),
+    ("offence", "offense"),
+    ("optimise", "optimize"),
+    ("organisation", "organization"),
+    ("organise", "organize"),
+    ("practise", "practice"),
+    ("programme", "program"),
+    ("realise", "realize"),
+    ("recognise", "recognize"),
+    ("signalling", "signaling"),
+    ("sub-license", "sublicense"),
+    ("sub license", "sublicense"),
+    ("utilisation", "utilization"),
+    ("whilst", "while"),
+    ("non-commercial", "noncommercial"),
+    ("per cent", "percent"),
+    ("copyright owner", "copyright holder"),
+];
+
+fn equivalent_words() -> &'static (Regex, HashMap<&'static str, &'static str>) {
+    static WORDS: OnceLock<(Regex, HashMap<&str, &str>)> = OnceLock::new();
+    WORDS.get_or_init(|| {
+        let variants: Vec<_> = EQUIVALENT_WORDS
+            .iter()
+            .map(|(variant, _)| regex::escape(variant))
+            .collect();
+        let regex = Regex::new(&format!(r"\b(?:{})\b", variants.join("|"))).unwrap();
+        (regex, EQUIVALENT_WORDS.iter().copied().collect())
+    })
+}
+
+/// Normalizes case, whitespace, quotes, dashes, the copyright symbol, url
+/// schemes and equivalent spellings, so that texts differing only in these
+/// compare equal.
+pub fn normalize(text: &str) -> String {
+    let text: String = text
+        .to_lowercase()
+        .chars()
+        .map(|c| match c {
+            '\'' | '‘' | '’' | '‚' | '‛' | '“' | '”' | '„' | '‟' | '«' | '»' | '`' => {
+                '"'
+            }
+            '‐' | '‑' | '‒' | '–' | '—' | '―' | '−' => '-',
+            c => c,
+        })
+        .collect();
+    let text = text.replace('©', "(c)").replace("https://", "http://");
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let (regex, words) = equivalent_words();
+    regex
+        .replace_all(&text, |captures: &regex::Captures| words[&captures[0]])
+        .into_owned()
+}
+
+/// Sørensen–Dice coefficient of the word bigrams of two normalized texts,
+/// from 0 (nothing in common) to 1 (same words in the same order).
+pub fn similarity(a: &str, b: &str) -> f64 {
+    fn bigrams(text: &str) -> HashMap<(&str, &str), usize> {
+        let words: Vec<&str> = text.split(' ').collect();
+        let mut bigrams = HashMap::new();
+        for pair in words.windows(2) {
+            *bigrams.entry((pair[0], pair[1])).or_insert(0) += 1;
+        }
+        bigrams
+    }
+    let (a, b) = (bigrams(a), bigrams(b));
+    let total: usize = a.values().sum::<usize>() + b.values().sum::<usize>();
+    if total == 0 {
+        return 0.0;
+    }
+    let common: usize = a
+        .iter()
+        .map(|(bigram, count)| (*count).min(*b.get(bigram).unwrap_or(&0)))
+        .sum();
+    2.0 * common as f64 / total as f64
+}
+
+/// Regex of the variable parts of a template, which are too large to be
+/// compiled as is, e.g. `.{0,5000}` for copyright notices.
+fn var_regex(pattern: &str) -> String {
+    let bounded = Regex::new(r"\{\d*,(\d+)\}").unwrap();
+    let large = bounded
+        .captures_iter(pattern)
+        .any(|captures| captures[1].parse::<usize>().map_or(true, |max| max > 100));
+    if large || Regex::new(pattern).is_err() {
+        String::from(".*?")
+    } else {
+        format!("(?:{pattern})")
+    }
+}
+
+fn segments_regex(segments: &[Segment], regex: &mut String) {
+    for segment in segments {
+        match segment {
+            Segment::Text(text) => {
+                for word in normalize(text).split(' ').filter(|word| !word.is_empty()) {
+                    regex.push_str(&regex::escape(word));
+                    regex.push_str(r"\s*");
+                }
+            }
+            Segment::Var(var) => {
+                regex.push_str(&var_regex(&var.pattern));
+                regex.push_str(r"\s*");
+            }
+            Segment::Optional(segments) => {
+                regex.push_str("(?:");
+                segments_regex(segments, regex);
+                regex.push_str(")?");
+            }
+        }
+    }
+}
+
+impl Template {
+    /// A regex matching the normalized texts of the license, with any values
+    /// for its variables and with or without its optional parts. If not
+    /// `anchored`, it also matches texts containing the license text.
+    pub fn to_regex(&self, anchored: bool) -> Option<Regex> {
+        let mut regex = String::new();
+        if anchored {
+            regex.push_str(r"^\s*");
+        }
+        segments_regex(&self.segments, &mut regex);
+        if anchored {
+            regex.push('$');
+        }
+        RegexBuilder::new(&regex)
+            .case_insensitive(true)
+            .dot_matches_new_line(true)
+            .size_limit(1 << 26)
+            .build()
+            .ok()
+    }
+
+    /// Whether the normalized `text` is an exact match of the template.
+    pub fn matches(&self, text: &str) -> bool {
+        self.to_regex(true)
+            .is_some_and(|regex| regex.is_match(text))
+    }
+
+    /// Whether the normalized `text` contains the license text, e.g. along
+    /// with the texts of other licenses.
+    pub fn is_contained_in(&self, text: &str) -> bool {
+        self.to_regex(false)
+            .is_some_and(|regex| regex.is_match(text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIT_TEMPLATE: &str = "<<beginOptional>>MIT License<<endOptional>>\n\n\
+        <<var;name=\"copyright\";original=\"Copyright (c) <year> <copyright holders>\";match=\".{0,5000}\">>\n\n\
+        Permission is hereby granted, free of charge, to any person obtaining a copy of this software \
+        and associated documentation files (the \"Software\")\n\n\
+        <<var;name=\"bullet\";original=\"1.\";match=\".{0,20}\">> The above copyright notice";
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(
+            normalize("The  “Software”\n\tis © —\r\nhttps://example.com Licence"),
+            "the \"software\" is (c) - http://example.com license"
+        );
+    }
+
+    #[test]
+    fn test_equivalent_words() {
+        assert_eq!(normalize("Analogue licence"), "analog license");
+        assert_eq!(
+            normalize("analog sublicence per cent"),
+            "analog sublicence percent"
+        );
+    }
+
+    #[test]
+    fn test_similarity() {
+        let text = normalize("Permission is hereby granted, free of charge");
+        assert_eq!(similarity(&text, &text), 1.0);
+        assert_eq!(similarity(&text, "nothing in common"), 0.0);
+        let other = normalize("Permission is hereby granted, without charge");
+        assert!(similarity(&text, &other) > 0.5);
+    }
+
+    #[test]
+    fn test_matches() {
+        let template = Template::parse(MIT_TEMPLATE).unwrap();
+        let text = "Copyright © 2023 Nyakku Shigure\n\n\
+            Permission is hereby granted, free of charge, to any person obtaining a copy of this\n\
+            software and associated documentation files (the 'Software')\n\n\
+            a) The above copyright notice";
+        assert!(template.matches(&normalize(text)));
+        let text = format!("MIT License\n\n{text}");
+        assert!(template.matches(&normalize(&text)));
+        let text = format!("{text}\n\nApache License");
+        assert!(!template.matches(&normalize(&text)));
+        assert!(template.is_contained_in(&normalize(&text)));
+        let text = text.replace("free of charge", "for a fee");
+        assert!(!template.is_contained_in(&normalize(&text)));
+    }
+}
@@ -1,2 +1,11 @@
+pub mod aliases;
+pub mod bundled;
+pub mod cache;
 pub mod details;
+pub mod error;
+pub mod exceptions;
+pub mod expression;
 pub mod list;
+pub mod matching;
+pub mod source;
+pub mod template;
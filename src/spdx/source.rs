@@ -0,0 +1,264 @@
+use super::error::Error;
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+pub const OFFICIAL_URL: &str = "https://spdx.org/licenses/";
+/// spdx.org only serves the latest version, tagged releases come from GitHub.
+pub const RELEASE_URL: &str =
+    "https://raw.githubusercontent.com/spdx/license-list-data/v{version}/json/";
+
+/// A single JSON document of the SPDX license list data.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Resource<'a> {
+    Licenses,
+    LicenseDetails(&'a str),
+    Exceptions,
+    ExceptionDetails(&'a str),
+}
+
+impl Resource<'_> {
+    /// Whether this is one of the lists, which carry the `licenseListVersion`.
+    pub fn is_list(&self) -> bool {
+        matches!(self, Resource::Licenses | Resource::Exceptions)
+    }
+
+    /// Path on the spdx.org website, relative to [`OFFICIAL_URL`].
+    fn website_path(&self) -> String {
+        match self {
+            Resource::Licenses => "licenses.json".into(),
+            Resource::LicenseDetails(id) | Resource::ExceptionDetails(id) => format!("{id}.json"),
+            Resource::Exceptions => "exceptions.json".into(),
+        }
+    }
+
+    /// Path relative to the `json` directory of spdx/license-list-data.
+    pub fn list_data_path(&self) -> String {
+        match self {
+            Resource::Licenses => "licenses.json".into(),
+            Resource::LicenseDetails(id) => format!("details/{id}.json"),
+            Resource::Exceptions => "exceptions.json".into(),
+            Resource::ExceptionDetails(id) => format!("exceptions/{id}.json"),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ListVersion {
+    license_list_version: String,
+}
+
+/// The `licenseListVersion` of a `licenses.json` or `exceptions.json`.
+pub fn list_version(list: &str) -> Option<String> {
+    serde_json::from_str::<ListVersion>(list)
+        .ok()
+        .map(|list| list.license_list_version)
+}
+
+/// Refuses a `licenses.json` or `exceptions.json` that doesn't belong to the pinned version.
+pub fn check_list_version(list: &str, expected: &str) -> Result<(), Error> {
+    match list_version(list) {
+        Some(found) if found == expected => Ok(()),
+        found => Err(Error::VersionMismatch {
+            expected: expected.into(),
+            found: found.unwrap_or_else(|| "unknown".into()),
+        }),
+    }
+}
+
+/// Where the SPDX license list data comes from.
+#[async_trait]
+pub trait LicenseSource: Send + Sync {
+    async fn fetch(&self, resource: Resource<'_>) -> Result<String, Error>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Layout {
+    /// https://spdx.org/licenses/
+    Website,
+    /// The `json` directory of spdx/license-list-data
+    ListData,
+}
+
+/// Fetches the data over HTTP(S), either from spdx.org or a mirror of
+/// spdx/license-list-data.
+#[derive(Debug)]
+pub struct HttpSource {
+    base_url: String,
+    layout: Layout,
+}
+
+impl HttpSource {
+    pub fn official() -> Self {
+        HttpSource {
+            base_url: OFFICIAL_URL.into(),
+            layout: Layout::Website,
+        }
+    }
+
+    /// A tagged release of spdx/license-list-data.
+    pub fn release(version: &str) -> Self {
+        HttpSource::mirror(&RELEASE_URL.replace("{version}", version))
+    }
+
+    /// `base_url` points to the `json` directory of a spdx/license-list-data mirror,
+    /// a `{version}` placeholder in it is replaced by the pinned version.
+    pub fn mirror(base_url: &str) -> Self {
+        HttpSource {
+            base_url: format!("{}/", base_url.trim_end_matches('/')),
+            layout: Layout::ListData,
+        }
+    }
+
+    fn url(&self, resource: Resource<'_>) -> String {
+        let path = match self.layout {
+            Layout::Website => resource.website_path(),
+            Layout::ListData => resource.list_data_path(),
+        };
+        format!("{}{path}", self.base_url)
+    }
+}
+
+#[async_trait]
+impl LicenseSource for HttpSource {
+    async fn fetch(&self, resource: Resource<'_>) -> Result<String, Error> {
+        let resp = reqwest::get(self.url(resource)).await?.error_for_status()?;
+        Ok(resp.text().await?)
+    }
+}
+
+/// Reads the data from a local checkout of spdx/license-list-data.
+#[derive(Debug)]
+pub struct DirSource {
+    root: PathBuf,
+    pinned: Option<String>,
+}
+
+impl DirSource {
+    /// `root` is the `json` directory of the checkout.
+    pub fn new(root: PathBuf) -> Self {
+        DirSource { root, pinned: None }
+    }
+
+    pub fn pin(mut self, version: Option<String>) -> Self {
+        self.pinned = version;
+        self
+    }
+}
+
+#[async_trait]
+impl LicenseSource for DirSource {
+    async fn fetch(&self, resource: Resource<'_>) -> Result<String, Error> {
+        let path = self.root.join(resource.list_data_path());
+        let text = fs::read_to_string(&path).map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => Error::Missing(path.display().to_string()),
+            _ => Error::Io(path, e),
+        })?;
+        if let (true, Some(pinned)) = (resource.is_list(), &self.pinned) {
+            check_list_version(&text, pinned)?;
+        }
+        Ok(text)
+    }
+}
+
+/// In-memory data, so that tests don't depend on the network.
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub struct FixtureSource {
+    files: std::collections::HashMap<String, String>,
+}
+
+#[cfg(test)]
+impl FixtureSource {
+    pub fn with(mut self, resource: Resource<'_>, text: &str) -> Self {
+        self.files.insert(resource.list_data_path(), text.into());
+        self
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl LicenseSource for FixtureSource {
+    async fn fetch(&self, resource: Resource<'_>) -> Result<String, Error> {
+        let path = resource.list_data_path();
+        self.files.get(&path).cloned().ok_or(Error::Missing(path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_http_source_url() {
+        let official = HttpSource::official();
+        assert_eq!(
+            official.url(Resource::Licenses),
+            "https://spdx.org/licenses/licenses.json"
+        );
+        assert_eq!(
+            official.url(Resource::LicenseDetails("MIT")),
+            "https://spdx.org/licenses/MIT.json"
+        );
+        assert_eq!(
+            official.url(Resource::ExceptionDetails("LLVM-exception")),
+            "https://spdx.org/licenses/LLVM-exception.json"
+        );
+        let mirror = HttpSource::mirror("https://example.com/license-list-data/json/");
+        assert_eq!(
+            mirror.url(Resource::LicenseDetails("MIT")),
+            "https://example.com/license-list-data/json/details/MIT.json"
+        );
+        assert_eq!(
+            mirror.url(Resource::ExceptionDetails("LLVM-exception")),
+            "https://example.com/license-list-data/json/exceptions/LLVM-exception.json"
+        );
+        let release = HttpSource::release("3.21");
+        assert_eq!(
+            release.url(Resource::Licenses),
+            "https://raw.githubusercontent.com/spdx/license-list-data/v3.21/json/licenses.json"
+        );
+    }
+
+    #[test]
+    fn test_check_list_version() {
+        let licenses = r#"{"licenseListVersion": "3.21"}"#;
+        assert!(check_list_version(licenses, "3.21").is_ok());
+        assert!(matches!(
+            check_list_version(licenses, "3.16"),
+            Err(Error::VersionMismatch { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_dir_source() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("details")).unwrap();
+        fs::write(dir.path().join("details/MIT.json"), "{}").unwrap();
+        let source = DirSource::new(dir.path().into());
+        let text = source.fetch(Resource::LicenseDetails("MIT")).await.unwrap();
+        assert_eq!(text, "{}");
+        let missing = source.fetch(Resource::Licenses).await;
+        assert!(matches!(missing, Err(Error::Missing(_))));
+
+        fs::write(
+            dir.path().join("licenses.json"),
+            r#"{"licenseListVersion": "3.16"}"#,
+        )
+        .unwrap();
+        let source = source.pin(Some("3.21".into()));
+        let mismatch = source.fetch(Resource::Licenses).await;
+        assert!(matches!(mismatch, Err(Error::VersionMismatch { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_fixture_source() {
+        let source = FixtureSource::default().with(Resource::Licenses, "{}");
+        assert_eq!(source.fetch(Resource::Licenses).await.unwrap(), "{}");
+        let missing = source.fetch(Resource::LicenseDetails("MIT")).await;
+        assert!(matches!(missing, Err(Error::Missing(_))));
+    }
+}
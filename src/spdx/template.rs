@@ -0,0 +1,329 @@
+//! SPDX license templates, see
+//! https://spdx.github.io/spdx-spec/v2.3/license-matching-guidelines-and-templates/
+
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Segment {
+    Text(String),
+    Var(Var),
+    /// `<<beginOptional>>...<<endOptional>>`
+    Optional(Vec<Segment>),
+}
+
+/// `<<var;name="...";original="...";match="...">>`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Var {
+    pub name: String,
+    /// Text of the variable in the license text
+    pub original: String,
+    /// Regex matching the allowed replacements
+    pub pattern: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    pub segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateError {
+    /// Byte offset in the parsed template
+    pub position: usize,
+    pub message: String,
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid license template at position {}: {}",
+            self.position, self.message
+        )
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+/// Copyright holder and year to fill into the `copyright` variables.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Copyright {
+    pub holder: Option<String>,
+    pub year: Option<String>,
+}
+
+impl Copyright {
+    pub fn is_empty(&self) -> bool {
+        self.holder.is_none() && self.year.is_none()
+    }
+
+    /// `Copyright (c) <year> <holder>` with the known parts.
+    pub fn line(&self) -> Option<String> {
+        if self.is_empty() {
+            return None;
+        }
+        let parts: Vec<&str> = [&self.year, &self.holder]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .collect();
+        Some(format!("Copyright (c) {}", parts.join(" ")))
+    }
+
+    /// Fills the values missing in `self` from `other`.
+    pub fn or(self, other: Copyright) -> Copyright {
+        Copyright {
+            holder: self.holder.or(other.holder),
+            year: self.year.or(other.year),
+        }
+    }
+
+    /// Fills the `<year>`, `[name of copyright owner]`, `yyyy`, ... placeholders
+    /// in the original text of a copyright variable. A copyright line without
+    /// placeholders, such as `Copyright (c) 2004-2010 by Internet Systems
+    /// Consortium, Inc.`, is part of the license and is not filled.
+    pub fn fill(&self, original: &str) -> Option<String> {
+        let mut filled = String::new();
+        let mut placeholders = false;
+        let mut rest = original;
+        while let Some(start) = rest.find(['<', '[']) {
+            let close = if rest[start..].starts_with('<') {
+                '>'
+            } else {
+                ']'
+            };
+            let Some(len) = rest[start..].find(close) else {
+                break;
+            };
+            let placeholder = &rest[start..start + len + 1];
+            let name = placeholder.to_lowercase();
+            let value = if name.contains("year") || name.contains("yyyy") {
+                &self.year
+            } else {
+                &self.holder
+            };
+            filled.push_str(&rest[..start]);
+            filled.push_str(value.as_deref().unwrap_or(placeholder));
+            placeholders = true;
+            rest = &rest[start + len + 1..];
+        }
+        filled.push_str(rest);
+        if placeholders {
+            return Some(filled);
+        }
+        // `yyyy name of author` in the GPL headers
+        if let Some((before, _)) = original.split_once("yyyy") {
+            let year = self.year.as_deref().unwrap_or("yyyy");
+            return match &self.holder {
+                Some(holder) => Some(format!("{before}{year} {holder}")),
+                None => Some(original.replacen("yyyy", year, 1)),
+            };
+        }
+        None
+    }
+}
+
+impl Template {
+    pub fn parse(text: &str) -> Result<Self, TemplateError> {
+        let mut stack: Vec<Vec<Segment>> = vec![Vec::new()];
+        let mut pos = 0;
+        while let Some(start) = text[pos..].find("<<").map(|i| pos + i) {
+            if start > pos {
+                let segment = Segment::Text(text[pos..start].to_string());
+                stack.last_mut().unwrap().push(segment);
+            }
+            let tag = &text[start + 2..];
+            if tag.starts_with("var;") {
+                let (var, end) = parse_var(text, start)?;
+                stack.last_mut().unwrap().push(Segment::Var(var));
+                pos = end;
+            } else if tag.starts_with("beginOptional") {
+                let end = find_end(text, start)?;
+                stack.push(Vec::new());
+                pos = end;
+            } else if tag.starts_with("endOptional>>") {
+                if stack.len() == 1 {
+                    return Err(TemplateError {
+                        position: start,
+                        message: "unmatched <<endOptional>>".into(),
+                    });
+                }
+                let segments = stack.pop().unwrap();
+                stack.last_mut().unwrap().push(Segment::Optional(segments));
+                pos = start + "<<endOptional>>".len();
+            } else {
+                // Not a template rule, e.g. `<<` in a shell snippet.
+                stack.last_mut().unwrap().push(Segment::Text("<<".into()));
+                pos = start + 2;
+            }
+        }
+        if pos < text.len() {
+            let segment = Segment::Text(text[pos..].to_string());
+            stack.last_mut().unwrap().push(segment);
+        }
+        if stack.len() > 1 {
+            return Err(TemplateError {
+                position: text.len(),
+                message: "unclosed <<beginOptional>>".into(),
+            });
+        }
+        Ok(Template {
+            segments: stack.pop().unwrap(),
+        })
+    }
+
+    /// Renders the template, with optional parts included and variables
+    /// replaced by `fill`, or by their original text when it returns `None`.
+    pub fn render(&self, fill: &dyn Fn(&Var) -> Option<String>) -> String {
+        let mut text = String::new();
+        render_segments(&self.segments, fill, &mut text);
+        text
+    }
+}
+
+fn render_segments(segments: &[Segment], fill: &dyn Fn(&Var) -> Option<String>, text: &mut String) {
+    for segment in segments {
+        match segment {
+            Segment::Text(s) => text.push_str(s),
+            Segment::Var(var) => match fill(var) {
+                Some(value) => text.push_str(&value),
+                None => text.push_str(&var.original),
+            },
+            Segment::Optional(segments) => render_segments(segments, fill, text),
+        }
+    }
+}
+
+fn find_end(text: &str, start: usize) -> Result<usize, TemplateError> {
+    text[start..]
+        .find(">>")
+        .map(|i| start + i + 2)
+        .ok_or_else(|| TemplateError {
+            position: start,
+            message: "unclosed rule".into(),
+        })
+}
+
+/// Parses the `<<var;...>>` rule at `start`, returning it with the offset
+/// right after it. Attribute values are quoted and may contain `;` and `>`.
+fn parse_var(text: &str, start: usize) -> Result<(Var, usize), TemplateError> {
+    let error = |position, message: &str| TemplateError {
+        position,
+        message: message.into(),
+    };
+    let mut var = Var {
+        name: String::new(),
+        original: String::new(),
+        pattern: String::new(),
+    };
+    let mut pos = start + "<<var".len();
+    loop {
+        if text[pos..].starts_with(">>") {
+            return Ok((var, pos + 2));
+        }
+        if !text[pos..].starts_with(';') {
+            return Err(error(pos, "expected `;` or `>>` in <<var>>"));
+        }
+        pos += 1;
+        let eq = text[pos..]
+            .find("=\"")
+            .ok_or_else(|| error(pos, "expected an attribute in <<var>>"))?;
+        let key = &text[pos..pos + eq];
+        let value_start = pos + eq + 2;
+        // The value ends at the first quote followed by the next attribute or the end of the rule.
+        let value_len = text[value_start..]
+            .match_indices('"')
+            .map(|(i, _)| i)
+            .find(|&i| {
+                let after = &text[value_start + i + 1..];
+                after.starts_with(";") || after.starts_with(">>")
+            })
+            .ok_or_else(|| error(value_start, "unclosed attribute value in <<var>>"))?;
+        let value = text[value_start..value_start + value_len].to_string();
+        match key {
+            "name" => var.name = value,
+            "original" => var.original = value,
+            "match" => var.pattern = value,
+            _ => {}
+        }
+        pos = value_start + value_len + 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIT: &str = "<<beginOptional>>MIT License<<endOptional>>\n\n\
+        <<var;name=\"copyright\";original=\"Copyright (c) <year> <copyright holders>\";match=\".{0,5000}\">>\n\n\
+        Permission is hereby granted";
+
+    #[test]
+    fn test_parse() {
+        let template = Template::parse(MIT).unwrap();
+        assert_eq!(
+            template.segments,
+            vec![
+                Segment::Optional(vec![Segment::Text("MIT License".into())]),
+                Segment::Text("\n\n".into()),
+                Segment::Var(Var {
+                    name: "copyright".into(),
+                    original: "Copyright (c) <year> <copyright holders>".into(),
+                    pattern: ".{0,5000}".into(),
+                }),
+                Segment::Text("\n\nPermission is hereby granted".into()),
+            ]
+        );
+        assert_eq!(
+            template.render(&|_| None),
+            "MIT License\n\nCopyright (c) <year> <copyright holders>\n\nPermission is hereby granted"
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Template::parse("<<beginOptional>>MIT").is_err());
+        assert!(Template::parse("MIT<<endOptional>>").is_err());
+        assert!(Template::parse("<<var;name=\"copyright").is_err());
+    }
+
+    #[test]
+    fn test_copyright() {
+        let copyright = Copyright {
+            holder: Some("Nyakku Shigure".into()),
+            year: Some("2023".into()),
+        };
+        assert_eq!(
+            copyright.fill("Copyright (c) <year> <copyright holders>"),
+            Some("Copyright (c) 2023 Nyakku Shigure".into())
+        );
+        assert_eq!(
+            copyright.fill("[yyyy] [name of copyright owner]"),
+            Some("2023 Nyakku Shigure".into())
+        );
+        assert_eq!(
+            copyright.fill("Copyright (c) 2004-2010 by Internet Systems Consortium"),
+            None
+        );
+        assert_eq!(
+            copyright.fill("yyyy name of author"),
+            Some("2023 Nyakku Shigure".into())
+        );
+        assert_eq!(copyright.fill("one line to give the program's name"), None);
+
+        assert_eq!(
+            copyright.line(),
+            Some("Copyright (c) 2023 Nyakku Shigure".into())
+        );
+        assert_eq!(Copyright::default().line(), None);
+
+        let copyright = Copyright {
+            holder: Some("Nyakku Shigure".into()),
+            year: None,
+        };
+        assert_eq!(
+            copyright.fill("Copyright (c) <year> <copyright holders>"),
+            Some("Copyright (c) <year> Nyakku Shigure".into())
+        );
+    }
+}